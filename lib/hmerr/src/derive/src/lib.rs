@@ -0,0 +1,430 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::ParseStream;
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, LitStr};
+
+/// `Error`, `Display` in the hmerr layout and `hmerr::Diagnostic`
+///
+/// on the struct or enum:
+/// * `#[into]` - conversions into `hmerr::GenericError` and `hmerr::ParseFileError`,
+///   the `#[source]` fields must then be `Into<Box<dyn Error + Send + Sync>>` |optional|
+///
+/// on the struct or on each variant:
+/// * `#[error("...")]` - message, formatted like `format!` with the fields in scope
+///   (`{0}` for a tuple field)
+/// * `#[help("...")]` - help message, shown as written |optional|
+/// * `#[code("...")]` - stable code shown in the header, see `hmerr::explain` |optional|
+///
+/// on a field (each at most once):
+/// * `#[source]` - error that caused this one, `E` or `Option<E>`
+/// * `#[file]` - file the error happened in, `impl AsRef<str>` or `Option<String>`
+/// * `#[line]` - `hmerr::parse::Line` or `Option<Line>`
+/// * `#[help]` - help message built at runtime, instead of `#[help("...")]`
+#[proc_macro_derive(HmError, attributes(into, error, help, code, source, file, line))]
+pub fn hm_error(input: TokenStream) -> TokenStream {
+	let input = syn::parse_macro_input!(input as DeriveInput);
+
+	match expand(&input) {
+		Ok(t) => t.into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+struct Variant {
+	pattern: TokenStream2,
+	error: TokenStream2, // arguments of `format!`
+	help: Option<Help>,
+	code: Option<LitStr>,
+	source: Option<Field>,
+	file: Option<Field>,
+	line: Option<Field>,
+}
+
+enum Help {
+	Lit(LitStr),
+	Field(Field),
+}
+
+struct Field {
+	binding: Ident,
+	option: bool,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+	let name = &input.ident;
+	let variant = match &input.data {
+		Data::Struct(s) => vec![variant(
+			quote!(#name),
+			&input.ident,
+			&input.attrs,
+			&s.fields,
+		)?],
+		Data::Enum(e) => e
+			.variants
+			.iter()
+			.map(|v| {
+				let variant_name = &v.ident;
+				variant(
+					quote!(#name::#variant_name),
+					variant_name,
+					&v.attrs,
+					&v.fields,
+				)
+			})
+			.collect::<syn::Result<_>>()?,
+		Data::Union(_) => {
+			return Err(syn::Error::new_spanned(
+				&input.ident,
+				"`HmError` cannot be derived for a union",
+			));
+		}
+	};
+
+	let mut into = false;
+	for attr in input.attrs.iter().filter(|a| a.path().is_ident("into")) {
+		attr.meta.require_path_only()?;
+		if into {
+			return Err(syn::Error::new_spanned(attr, "duplicate `#[into]`"));
+		}
+		into = true;
+	}
+
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+	let arm = |f: &dyn Fn(&Variant) -> TokenStream2| {
+		let arm = variant.iter().map(|v| {
+			let pattern = &v.pattern;
+			let body = f(v);
+			quote!(#pattern => #body,)
+		});
+		quote!(match self { #(#arm)* })
+	};
+	let arm_by_value = |f: &dyn Fn(&Variant) -> TokenStream2| {
+		let arm = variant.iter().map(|v| {
+			let pattern = &v.pattern;
+			let body = f(v);
+			quote!(#pattern => #body,)
+		});
+		quote!(match e { #(#arm)* })
+	};
+
+	let source = arm(&|v| match &v.source {
+		Some(Field {
+			binding,
+			option: false,
+		}) => quote!(::core::option::Option::Some(#binding.as_dyn_error())),
+		Some(Field {
+			binding,
+			option: true,
+		}) => quote!(#binding.as_ref().map(|s| s.as_dyn_error())),
+		None => quote!(::core::option::Option::None),
+	});
+
+	let message = arm(&|v| {
+		let error = &v.error;
+		quote!(::std::borrow::Cow::Owned(::std::format!(#error)))
+	});
+
+	let help = arm(&|v| match &v.help {
+		Some(Help::Lit(lit)) => quote!(::core::option::Option::Some(#lit)),
+		Some(Help::Field(f)) => as_str(f),
+		None => quote!(::core::option::Option::None),
+	});
+
+	let code = arm(&|v| match &v.code {
+		Some(code) => quote!(::core::option::Option::Some(#code)),
+		None => quote!(::core::option::Option::None),
+	});
+
+	let location = arm(&|v| {
+		if v.file.is_none() && v.line.is_none() {
+			return quote!(::core::option::Option::None);
+		}
+		let file = v
+			.file
+			.as_ref()
+			.map_or(quote!(::core::option::Option::None), as_str);
+		let line = match &v.line {
+			Some(Field {
+				binding,
+				option: false,
+			}) => quote!(::core::option::Option::Some(#binding)),
+			Some(Field {
+				binding,
+				option: true,
+			}) => quote!(#binding.as_ref()),
+			None => quote!(::core::option::Option::None),
+		};
+		quote!(::core::option::Option::Some(hmerr::diagnostic::Location {
+			file: #file,
+			line: #line,
+			snippet: ::core::option::Option::None,
+		}))
+	});
+
+	let into_source = |v: &Variant| match &v.source {
+		Some(Field {
+			binding,
+			option: false,
+		}) => quote!(::core::option::Option::Some(::core::convert::Into::into(#binding))),
+		Some(Field {
+			binding,
+			option: true,
+		}) => quote!(#binding.map(::core::convert::Into::into)),
+		None => quote!(::core::option::Option::None),
+	};
+	let generic_source = arm_by_value(&into_source);
+	let parse_field = arm_by_value(&|v| {
+		let file = match &v.file {
+			Some(Field {
+				binding,
+				option: false,
+			}) => quote!(::core::option::Option::Some(
+				::core::convert::AsRef::<str>::as_ref(&#binding).to_string()
+			)),
+			Some(Field {
+				binding,
+				option: true,
+			}) => quote!(#binding.map(::core::convert::Into::into)),
+			None => quote!(::core::option::Option::None),
+		};
+		let line = match &v.line {
+			Some(Field {
+				binding,
+				option: false,
+			}) => quote!(::core::option::Option::Some(#binding)),
+			Some(Field {
+				binding,
+				option: true,
+			}) => quote!(#binding),
+			None => quote!(::core::option::Option::None),
+		};
+		let source = into_source(v);
+		quote!((#file, #line, #source))
+	});
+
+	// a variant binds all its fields, even the ones it does not use
+	let diagnostic = quote! {
+		#[allow(unused_variables)]
+		impl #impl_generics ::std::error::Error for #name #ty_generics #where_clause {
+			fn source(&self) -> ::core::option::Option<&(dyn ::std::error::Error + 'static)> {
+				use hmerr::__AsDynError as _;
+				#source
+			}
+		}
+
+		impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				hmerr::diagnostic::render(f, self)
+			}
+		}
+
+		#[allow(unused_variables)]
+		impl #impl_generics hmerr::Diagnostic for #name #ty_generics #where_clause {
+			fn message(&self) -> ::std::borrow::Cow<'_, str> {
+				#message
+			}
+
+			fn code(&self) -> ::core::option::Option<&str> {
+				#code
+			}
+
+			fn help(&self) -> ::core::option::Option<&str> {
+				#help
+			}
+
+			fn location(&self) -> ::core::option::Option<hmerr::diagnostic::Location<'_>> {
+				#location
+			}
+		}
+	};
+	if !into {
+		return Ok(diagnostic);
+	}
+
+	// opt-in, a source that is not `Send + Sync` (`Box<dyn Error>`) cannot be converted
+	Ok(quote! {
+		#diagnostic
+
+		#[allow(unused_variables)]
+		impl #impl_generics ::core::convert::From<#name #ty_generics> for hmerr::GenericError #where_clause {
+			fn from(e: #name #ty_generics) -> Self {
+				let error = hmerr::Diagnostic::message(&e).into_owned();
+				let help = hmerr::Diagnostic::help(&e).map(::std::string::String::from);
+				let source = #generic_source;
+				Self {
+					error,
+					help,
+					source_file: ::core::option::Option::None,
+					source,
+				}
+			}
+		}
+
+		#[allow(unused_variables)]
+		impl #impl_generics ::core::convert::From<#name #ty_generics> for hmerr::ParseFileError #where_clause {
+			fn from(e: #name #ty_generics) -> Self {
+				let error = hmerr::Diagnostic::message(&e).into_owned();
+				let code = hmerr::Diagnostic::code(&e).map(::std::string::String::from);
+				let help = hmerr::Diagnostic::help(&e).map(::std::string::String::from);
+				let (file, line, source) = #parse_field;
+				Self {
+					error,
+					code,
+					help,
+					file,
+					line,
+					source,
+					..::core::default::Default::default()
+				}
+			}
+		}
+	})
+}
+
+/// `f` as an `Option<&str>`
+fn as_str(f: &Field) -> TokenStream2 {
+	let binding = &f.binding;
+	if f.option {
+		quote!(#binding.as_deref())
+	} else {
+		quote!(::core::option::Option::Some(::core::convert::AsRef::<str>::as_ref(#binding)))
+	}
+}
+
+fn variant(
+	path: TokenStream2,
+	name: &Ident,
+	attrs: &[Attribute],
+	fields: &Fields,
+) -> syn::Result<Variant> {
+	let mut error = None;
+	let mut help = None;
+	let mut code = None;
+	for attr in attrs {
+		if attr.path().is_ident("error") {
+			if error.is_some() {
+				return Err(syn::Error::new_spanned(attr, "duplicate `#[error]`"));
+			}
+			error = Some(attr.parse_args_with(|input: ParseStream| {
+				let lit: LitStr = input.parse()?;
+				let rest: TokenStream2 = input.parse()?;
+				Ok((lit, rest))
+			})?);
+		} else if attr.path().is_ident("help") {
+			if help.is_some() {
+				return Err(syn::Error::new_spanned(attr, "duplicate `#[help]`"));
+			}
+			help = Some(Help::Lit(attr.parse_args::<LitStr>()?));
+		} else if attr.path().is_ident("code") {
+			if code.is_some() {
+				return Err(syn::Error::new_spanned(attr, "duplicate `#[code]`"));
+			}
+			code = Some(attr.parse_args::<LitStr>()?);
+		}
+	}
+	let Some((lit, rest)) = error else {
+		return Err(syn::Error::new_spanned(
+			name,
+			"missing `#[error(\"...\")]` message",
+		));
+	};
+
+	let mut source = None;
+	let mut file = None;
+	let mut line = None;
+	let mut binding = Vec::new();
+	for (i, field) in fields.iter().enumerate() {
+		let b = field
+			.ident
+			.clone()
+			.unwrap_or_else(|| format_ident!("_{}", i));
+		binding.push(b.clone());
+
+		let mut role = 0;
+		for attr in &field.attrs {
+			let slot = if attr.path().is_ident("source") {
+				&mut source
+			} else if attr.path().is_ident("file") {
+				&mut file
+			} else if attr.path().is_ident("line") {
+				&mut line
+			} else if attr.path().is_ident("help") {
+				if help.is_some() {
+					return Err(syn::Error::new_spanned(attr, "duplicate `#[help]`"));
+				}
+				help = Some(Help::Field(Field {
+					binding: b.clone(),
+					option: is_option(&field.ty),
+				}));
+				continue;
+			} else {
+				continue;
+			};
+			if slot.is_some() {
+				return Err(syn::Error::new_spanned(
+					attr,
+					"only one field can have this attribute",
+				));
+			}
+			role += 1;
+			if role > 1 {
+				return Err(syn::Error::new_spanned(
+					attr,
+					"a field can only be one of `#[source]`, `#[file]` and `#[line]`",
+				));
+			}
+			*slot = Some(Field {
+				binding: b.clone(),
+				option: is_option(&field.ty),
+			});
+		}
+	}
+
+	let pattern = match fields {
+		Fields::Named(_) => quote!(#path { #(#binding),* }),
+		Fields::Unnamed(_) => quote!(#path ( #(#binding),* )),
+		Fields::Unit => path,
+	};
+	let lit = if matches!(fields, Fields::Unnamed(_)) {
+		LitStr::new(&positional(&lit.value()), lit.span())
+	} else {
+		lit
+	};
+
+	Ok(Variant {
+		pattern,
+		error: quote!(#lit #rest),
+		help,
+		code,
+		source,
+		file,
+		line,
+	})
+}
+
+/// `{0}` to `{_0}`, so a tuple field is captured by its binding
+fn positional(s: &str) -> String {
+	let mut out = String::with_capacity(s.len());
+	let mut chars = s.chars().peekable();
+	while let Some(c) = chars.next() {
+		out.push(c);
+		if c != '{' {
+			continue;
+		}
+		match chars.peek() {
+			Some('{') => out.push(chars.next().unwrap_or('{')),
+			Some(d) if d.is_ascii_digit() => out.push('_'),
+			_ => {}
+		}
+	}
+	out
+}
+
+fn is_option(ty: &syn::Type) -> bool {
+	match ty {
+		syn::Type::Path(p) => p.path.segments.last().is_some_and(|s| s.ident == "Option"),
+		_ => false,
+	}
+}
@@ -0,0 +1,216 @@
+use super::Label;
+
+use crate::display::{RESET, Theme, char_width, width};
+
+use std::ops::Range;
+
+/// rows of characters, each tagged with whether it belongs to a primary label
+#[derive(Default)]
+pub(super) struct Canvas(pub Vec<Vec<(char, Option<bool>)>>);
+
+impl Canvas {
+	pub fn put(&mut self, row: usize, col: usize, c: char, primary: bool) {
+		if self.0.len() <= row {
+			self.0.resize(row + 1, Vec::new());
+		}
+		let row = &mut self.0[row];
+		if row.len() <= col {
+			row.resize(col + 1, (' ', None));
+		}
+		row[col] = (c, Some(primary));
+	}
+
+	pub fn text(&mut self, row: usize, col: usize, s: &str, primary: bool) {
+		for (i, c) in s.chars().enumerate() {
+			self.put(row, col + i, c, primary);
+		}
+	}
+
+	/// rustc-style labels, every column shifted by `offset`:
+	///
+	/// ```text
+	/// key val val
+	///     ^^^ --- defined here
+	///     |
+	///     expected u8
+	/// ```
+	pub fn label(t: &Theme, label: &[Label], offset: usize) -> Self {
+		let connector = connector(t);
+
+		let mut label: Vec<&Label> = label.iter().collect();
+		label.sort_by_key(|l| l.range.start);
+
+		let mut canvas = Self::default();
+		// secondary first, so primary wins where they overlap
+		for l in label
+			.iter()
+			.filter(|l| !l.primary)
+			.chain(label.iter().filter(|l| l.primary))
+		{
+			let sign = if l.primary {
+				t.lint_sign
+			} else {
+				t.secondary_sign
+			};
+			for col in l.range.start..l.range.end.max(l.range.start + 1) {
+				canvas.put(0, offset + col, sign, l.primary);
+			}
+		}
+
+		let mut message: Vec<&Label> = label
+			.iter()
+			.copied()
+			.filter(|l| l.message.is_some())
+			.collect();
+
+		// the rightmost message stays on the underline if nothing is highlighted after its start
+		if let Some(last) = message.last().copied()
+			&& label
+				.iter()
+				.all(|l| std::ptr::eq(*l, last) || l.range.end <= last.range.start)
+		{
+			let end = label
+				.iter()
+				.map(|l| l.range.end.max(l.range.start + 1))
+				.max();
+			canvas.text(
+				0,
+				offset + end.unwrap_or_default() + 1,
+				&text(last.message.as_deref()),
+				last.primary,
+			);
+			message.pop();
+		}
+
+		if !message.is_empty() {
+			for m in &message {
+				canvas.put(1, offset + m.range.start, connector, m.primary);
+			}
+			let mut row = 2;
+			while let Some(m) = message.pop() {
+				for other in &message {
+					canvas.put(row, offset + other.range.start, connector, other.primary);
+				}
+				canvas.text(
+					row,
+					offset + m.range.start,
+					&text(m.message.as_deref()),
+					m.primary,
+				);
+				row += 1;
+			}
+		}
+
+		canvas
+	}
+}
+
+/// display column of the byte `byte` of `line`, as rendered (tabs and spaces shown as their sign)
+///
+/// a byte inside a character is rounded down to the start of the character (up if `up`),
+/// a byte past the end of the line is one column after the line
+pub(super) fn column(t: &Theme, line: &str, byte: usize, up: bool) -> usize {
+	if byte > line.len() {
+		return column(t, line, line.len(), up) + 1;
+	}
+
+	let mut byte = byte;
+	while !line.is_char_boundary(byte) {
+		if up {
+			byte += 1;
+		} else {
+			byte -= 1;
+		}
+	}
+
+	line[..byte]
+		.chars()
+		.map(|c| match c {
+			'\t' => width(&t.tab_sign),
+			' ' => width(&t.space_sign),
+			c => char_width(c),
+		})
+		.sum()
+}
+
+/// byte range of `line` to display columns, never empty if `r` is not
+pub(super) fn columns(t: &Theme, line: &str, r: &Range<usize>) -> Range<usize> {
+	let start = column(t, line, r.start.min(line.len()), false);
+	let end = column(t, line, r.end, true).max(start);
+	if end == start && r.end > r.start {
+		start..start + 1
+	} else {
+		start..end
+	}
+}
+
+/// `label` with its byte ranges in `line` turned into display columns
+pub(super) fn label_columns(t: &Theme, line: &str, label: &[Label]) -> Vec<Label> {
+	label
+		.iter()
+		.map(|l| Label {
+			range: columns(t, line, &l.range),
+			..l.clone()
+		})
+		.collect()
+}
+
+/// vertical line joining a label to its message
+pub(super) fn connector(t: &Theme) -> char {
+	t.side_sign.chars().next().unwrap_or('|')
+}
+
+/// message on a single line
+pub(super) fn text(message: Option<&str>) -> String {
+	message.unwrap_or_default().replace('\n', " ")
+}
+
+/// `row` colored as its labels, `width` is the minimum number of columns written
+pub(super) fn write_row(
+	f: &mut std::fmt::Formatter<'_>,
+	t: &Theme,
+	row: &[(char, Option<bool>)],
+	width: usize,
+) -> std::fmt::Result {
+	let mut current = None;
+	for (c, primary) in row {
+		if primary.is_some() && *primary != current {
+			let color = if *primary == Some(true) {
+				&t.lint
+			} else {
+				&t.side
+			};
+			write!(f, "{RESET}{color}")?;
+			current = *primary;
+		}
+		write!(f, "{c}")?;
+	}
+	if current.is_some() {
+		write!(f, "{RESET}")?;
+	}
+	write!(f, "{}", " ".repeat(width.saturating_sub(row.len())))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_columns() {
+		let t = Theme::ascii();
+
+		assert_eq!(columns(&t, "John 42", &(5..7)), 5..7);
+		// `é` is 2 bytes, 1 column
+		assert_eq!(columns(&t, "é 42", &(3..5)), 2..4);
+		// `日本` is 6 bytes, 4 columns
+		assert_eq!(columns(&t, "日本 42", &(0..6)), 0..4);
+		assert_eq!(columns(&t, "日本 42", &(7..9)), 5..7);
+		// inside a character, rounded to the whole character
+		assert_eq!(columns(&t, "日本 42", &(1..2)), 0..2);
+		// past the end, right after the line
+		assert_eq!(columns(&t, "key", &(3..4)), 3..4);
+		assert_eq!(columns(&t, "key", &(42..45)), 3..4);
+		// empty stays empty
+		assert_eq!(columns(&t, "key", &(1..1)), 1..1);
+	}
+}
@@ -0,0 +1,577 @@
+use super::canvas::{self, Canvas};
+use super::{Diagnostic, Label, Location};
+
+use crate::display::{self, RESET, Theme, theme, write};
+use crate::parse::{Highlight, Line, Snippet};
+use crate::source;
+use crate::suggestion::{Edit, Suggestion};
+
+/// render `d` in the hmerr layout (header, location, highlighted line or snippet, help, causes)
+///
+/// the `Display` of `GenericError` and `ParseFileError`
+pub fn render(f: &mut std::fmt::Formatter<'_>, d: &dyn Diagnostic) -> std::fmt::Result {
+	if let Some(result) = super::snapshot::capture(f, d) {
+		return result;
+	}
+
+	display::render(f, |f| {
+		let location = d.location();
+		let line = location.and_then(|l| l.line);
+		let snippet = location.and_then(|l| l.snippet);
+		let index = match snippet {
+			Some(s) => s.primary().map(|h| h.start.0),
+			None => line.and_then(|l| l.index),
+		};
+		let cause = d.caused_by();
+		let suggestion = d.suggestions();
+		let last = snippet.map_or(index, |s| Some(s.last())).max(
+			suggestion
+				.iter()
+				.flat_map(|s| &s.edit)
+				.map(|e| e.line)
+				.max(),
+		);
+		let padding = write::padding(cause.as_ref(), last);
+		let help = d.help();
+
+		write::code(f, d.severity(), d.code(), &d.message())?;
+		if let Some(location) = location {
+			write::file(f, &padding, location.file, index)?;
+		}
+		if let Some(snippet) = snippet {
+			w_snippet(f, &padding, snippet, cause.is_some())?;
+		} else if let Some(line) = line {
+			w_line(f, &padding, line, &d.labels(), cause.is_some())?;
+		}
+		if location.is_some() || help.is_some() {
+			write::help(f, &padding, help)?;
+		}
+		for s in suggestion {
+			w_suggestion(
+				f,
+				&padding,
+				s,
+				location.unwrap_or_default(),
+				last,
+				cause.is_some(),
+			)?;
+		}
+		write::source_file(f, &padding, d.source_file())?;
+		write::source(f, cause.as_ref())?;
+
+		Ok(())
+	})
+}
+
+fn w_line(
+	f: &mut std::fmt::Formatter<'_>,
+	padding: &str,
+	line: &Line,
+	label: &[Label],
+	source: bool,
+) -> std::fmt::Result {
+	let t = theme();
+	let content = visible(&t, &line.line);
+	let side_padding_sign = t.sign(&t.side_padding_sign);
+	let side_sign = t.sign(&t.side_sign);
+
+	writeln!(f, "\n{padding}{side_padding_sign}")?;
+
+	if let Some(index) = line.index {
+		if source {
+			write!(f, "{}", t.source_side())?;
+		}
+		writeln!(
+			f,
+			"{RESET}{side}{index} {side_sign}{content}",
+			side = t.side
+		)?;
+	} else {
+		writeln!(f, "{padding}{side_sign}{content}")?;
+	}
+
+	w_lint(f, padding, &canvas::label_columns(&t, &line.line, label))?;
+
+	write!(f, "{padding}{side_padding_sign}")
+}
+
+fn w_lint(f: &mut std::fmt::Formatter<'_>, padding: &str, label: &[Label]) -> std::fmt::Result {
+	if label.is_empty() {
+		return Ok(());
+	}
+	if label.iter().any(|l| !l.primary || l.message.is_some()) {
+		return w_label(f, padding, label);
+	}
+
+	// labels of a user `Diagnostic` might be unsorted or overlapping
+	let mut range: Vec<_> = label.iter().map(|l| l.range.clone()).collect();
+	range.sort_by_key(|r| r.start);
+
+	let t = theme();
+	let mut s = String::new();
+	let mut start = 0;
+	let lint_sign = t.lint_sign.to_string();
+	for r in range {
+		let from = r.start.max(start);
+		s.push_str(&" ".repeat(from - start));
+		s.push_str(&lint_sign.repeat(r.end.saturating_sub(from)));
+		start = start.max(r.end);
+	}
+	writeln!(
+		f,
+		"{padding}{side_sign}{RESET}{lint}{s}{RESET}",
+		side_sign = t.sign(&t.side_sign),
+		lint = t.lint
+	)?;
+
+	Ok(())
+}
+
+fn w_label(f: &mut std::fmt::Formatter<'_>, padding: &str, label: &[Label]) -> std::fmt::Result {
+	let t = theme();
+	let side_sign = t.sign(&t.side_sign);
+	for row in Canvas::label(&t, label, 0).0 {
+		write!(f, "{padding}{side_sign}")?;
+		canvas::write_row(f, &t, &row, 0)?;
+		writeln!(f)?;
+	}
+
+	Ok(())
+}
+
+/// every line of `snippet` with its gutter line number,
+/// a highlight across lines is drawn in a margin on the left:
+///
+/// ```text
+/// 1 │   block {
+///   │  _______^
+/// 2 │ │     b 2
+/// 3 │ │     c 3
+///   │ │_______^ unterminated block
+/// ```
+fn w_snippet(
+	f: &mut std::fmt::Formatter<'_>,
+	padding: &str,
+	snippet: &Snippet,
+	source: bool,
+) -> std::fmt::Result {
+	let t = theme();
+	let width = snippet.last().to_string().len();
+	let connector = canvas::connector(&t);
+	let side_sign = t.sign(&t.side_sign);
+	let side_padding_sign = t.sign(&t.side_padding_sign);
+
+	let multi: Vec<&Highlight> = snippet
+		.highlight
+		.iter()
+		.filter(|h| h.is_multiline())
+		.collect();
+	let margin = multi.len() * 2;
+	let sign = |h: &Highlight| {
+		if h.primary {
+			t.lint_sign
+		} else {
+			t.secondary_sign
+		}
+	};
+
+	writeln!(f, "\n{padding}{side_padding_sign}")?;
+
+	for (i, content) in snippet.line.iter().enumerate() {
+		let index = snippet.first + i;
+		let mut open: Vec<bool> = multi
+			.iter()
+			.map(|h| h.start.0 < index && index <= h.end.0)
+			.collect();
+		let bar = |canvas: &mut Canvas, row: usize, open: &[bool]| {
+			for (k, h) in multi.iter().enumerate() {
+				if open[k] {
+					canvas.put(row, 2 * k, connector, h.primary);
+				}
+			}
+		};
+
+		let mut line = Canvas::default();
+		bar(&mut line, 0, &open);
+		if source {
+			write!(f, "{}", t.source_side())?;
+		}
+		write!(f, "{RESET}{side}{index:>width$} {side_sign}", side = t.side)?;
+		canvas::write_row(f, &t, line.0.first().map_or(&[], Vec::as_slice), margin)?;
+		writeln!(f, "{}", visible(&t, content))?;
+
+		let mut below = Canvas::default();
+		let mut row = 0;
+		for (k, h) in multi.iter().enumerate() {
+			if h.start.0 != index {
+				continue;
+			}
+			let start = margin + canvas::column(&t, content, h.start.1, false);
+			for col in 2 * k + 1..start {
+				below.put(row, col, '_', h.primary);
+			}
+			below.put(row, start, sign(h), h.primary);
+			bar(&mut below, row, &open);
+			open[k] = true;
+			row += 1;
+		}
+		for (k, h) in multi.iter().enumerate() {
+			if h.end.0 != index {
+				continue;
+			}
+			// on the last character of the highlight
+			let last = h.end.1.saturating_sub(1)..h.end.1.max(1);
+			let end = margin + canvas::columns(&t, content, &last).start;
+			for col in 2 * k + 1..end {
+				below.put(row, col, '_', h.primary);
+			}
+			below.put(row, end, sign(h), h.primary);
+			if h.message.is_some() {
+				below.text(row, end + 2, &canvas::text(h.message.as_deref()), h.primary);
+			}
+			bar(&mut below, row, &open);
+			open[k] = false;
+			row += 1;
+		}
+
+		let label: Vec<Label> = snippet
+			.highlight
+			.iter()
+			.filter(|h| !h.is_multiline() && h.start.0 == index)
+			.map(|h| Label {
+				range: h.start.1..h.end.1,
+				message: h.message.clone(),
+				primary: h.primary,
+			})
+			.collect();
+		if !label.is_empty() {
+			let label = canvas::label_columns(&t, content, &label);
+			for (r, cells) in Canvas::label(&t, &label, margin).0.into_iter().enumerate() {
+				for (col, (c, primary)) in cells.into_iter().enumerate() {
+					if let Some(primary) = primary {
+						below.put(row + r, col, c, primary);
+					}
+				}
+				bar(&mut below, row + r, &open);
+			}
+		}
+
+		for r in below.0 {
+			write!(f, "{padding}{side_sign}")?;
+			canvas::write_row(f, &t, &r, 0)?;
+			writeln!(f)?;
+		}
+	}
+
+	write!(f, "{padding}{side_padding_sign}")
+}
+
+/// `suggestion` applied to the lines it changes,
+/// replaced text is marked with `~`, inserted with `+`, deleted with `-`:
+///
+/// ```text
+///  = suggested fix: did you mean `vt`?
+///  :
+/// 1 | vt 1 2
+///   | ~~
+///  :
+/// ```
+fn w_suggestion(
+	f: &mut std::fmt::Formatter<'_>,
+	padding: &str,
+	suggestion: &Suggestion,
+	location: Location,
+	last: Option<usize>,
+	source: bool,
+) -> std::fmt::Result {
+	let t = theme();
+	let width = last.unwrap_or_default().to_string().len();
+	let side_sign = t.sign(&t.side_sign);
+	let side_padding_sign = t.sign(&t.side_padding_sign);
+
+	write!(
+		f,
+		"\n{padding}{help_sign}{RESET}{c}suggested fix{RESET}",
+		help_sign = t.sign(&t.help_sign),
+		c = t.help
+	)?;
+	if let Some(message) = &suggestion.message {
+		write!(f, "{b}:{RESET} {message}", b = t.bold)?;
+	}
+
+	let mut edit: Vec<&Edit> = suggestion.edit.iter().collect();
+	edit.sort_by_key(|e| (e.line, e.range.start, e.range.end));
+	let mut written = false;
+	for line in edit.chunk_by(|a, b| a.line == b.line) {
+		let index = line[0].line;
+		let Some(content) = original(location, index) else {
+			continue;
+		};
+		if !written {
+			writeln!(f, "\n{padding}{side_padding_sign}")?;
+			written = true;
+		}
+
+		for (i, row) in fixed(&content, line).into_iter().enumerate() {
+			if source {
+				write!(f, "{}", t.source_side())?;
+			}
+			let gutter = if i == 0 {
+				index.to_string()
+			} else {
+				String::new()
+			};
+			let mut text = String::new();
+			let mut mark = String::new();
+			for (s, sign) in row {
+				let n = canvas::column(&t, &s, s.len(), false);
+				text.push_str(&visible(&t, &s));
+				match sign {
+					Some(sign) => {
+						let color = if sign == '-' { &t.got } else { &t.expected };
+						mark.push_str(&format!(
+							"{RESET}{color}{m}{RESET}",
+							m = sign.to_string().repeat(n)
+						));
+					}
+					None => mark.push_str(&" ".repeat(n)),
+				}
+			}
+			writeln!(
+				f,
+				"{RESET}{side}{gutter:>width$} {side_sign}{text}",
+				side = t.side
+			)?;
+			if mark.contains(RESET) {
+				writeln!(f, "{padding}{side_sign}{}", mark.trim_end())?;
+			}
+		}
+	}
+	if written {
+		write!(f, "{padding}{side_padding_sign}")?;
+	}
+
+	Ok(())
+}
+
+/// line `index` as shown by `location`, read from its file if not shown
+fn original(location: Location, index: usize) -> Option<String> {
+	if let Some(snippet) = location.snippet
+		&& let Some(line) = index
+			.checked_sub(snippet.first)
+			.and_then(|i| snippet.line.get(i))
+	{
+		return Some(line.clone());
+	}
+	if let Some(line) = location.line
+		&& line.index == Some(index)
+	{
+		return Some(line.line.clone());
+	}
+	source::cache().line(location.file?, index)
+}
+
+/// rows of `line` once `edit` are applied, as pieces of text with the sign marking them
+///
+/// a line with only deletions is shown as it was, with the deleted text marked
+fn fixed(line: &str, edit: &[&Edit]) -> Vec<Vec<(String, Option<char>)>> {
+	let floor = |mut i: usize| {
+		i = i.min(line.len());
+		while !line.is_char_boundary(i) {
+			i -= 1;
+		}
+		i
+	};
+	let delete = edit.iter().all(|e| e.text.is_empty());
+
+	let mut piece = Vec::new();
+	let mut last = 0;
+	for e in edit {
+		let start = floor(e.range.start).max(last);
+		let end = floor(e.range.end).max(start);
+		piece.push((line[last..start].to_string(), None));
+		if delete {
+			piece.push((line[start..end].to_string(), Some('-')));
+		} else if !e.text.is_empty() {
+			let sign = if start == end { '+' } else { '~' };
+			piece.push((e.text.clone(), Some(sign)));
+		}
+		last = end;
+	}
+	piece.push((line[last..].to_string(), None));
+
+	let mut row = vec![Vec::new()];
+	for (s, sign) in piece {
+		for (i, s) in s.split('\n').enumerate() {
+			if i > 0 {
+				row.push(Vec::new());
+			}
+			if !s.is_empty() {
+				row.last_mut().unwrap().push((s.to_string(), sign));
+			}
+		}
+	}
+	row
+}
+
+/// tabs and spaces of `s` made visible
+fn visible(t: &Theme, s: &str) -> String {
+	let tab = format!("{RESET}{c}{s}{RESET}", c = t.tab, s = t.tab_sign);
+	let space = format!("{RESET}{c}{s}{RESET}", c = t.space, s = t.space_sign);
+	s.replace("\t", &tab).replace(" ", &space)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::ParseFileError;
+	use crate::diagnostic::Label;
+	use crate::display::{Plain, Theme, Themed};
+	use crate::parse::Line;
+
+	fn render(wrong: Vec<crate::parse::Wrong>) -> String {
+		let e = ParseFileError::new(
+			"invalid value",
+			None,
+			Some(Line::new("key val val", Some(3), wrong)),
+			None::<String>,
+			None::<String>,
+			None,
+		);
+		Plain(Themed(&e, &Theme::ascii())).to_string()
+	}
+
+	#[test]
+	fn test_code() {
+		let e = ParseFileError::new("unsupported keyword", None, None, None, None, None)
+			.with_code("SPOF0002");
+		assert_eq!(
+			Plain(Themed(&e, &Theme::ascii())).to_string(),
+			"error[SPOF0002]:\tunsupported keyword"
+		);
+	}
+
+	#[test]
+	fn test_label_inline() {
+		let s = render(vec![
+			Label::primary(4..7, "expected u8").into(),
+			Label::secondary(8..11, "defined here").into(),
+		]);
+		assert!(s.contains("\n  |     ^^^ --- defined here\n  |     |\n  |     expected u8\n"));
+	}
+
+	#[test]
+	fn test_label_overlap() {
+		let s = render(vec![
+			Label::primary(4..11, "whole value").into(),
+			Label::secondary(8..11, "this part").into(),
+			(0, 3).into(),
+		]);
+		assert!(s.contains(concat!(
+			"\n  | ^^^ ^^^^^^^\n",
+			"  |     |   |\n",
+			"  |     |   this part\n",
+			"  |     whole value\n",
+		)));
+	}
+
+	#[test]
+	fn test_unicode_width() {
+		let render = |line: &str, wrong| {
+			let e = ParseFileError::new(
+				"invalid value",
+				None,
+				Some(Line::new(line, None, wrong)),
+				None::<String>,
+				None::<String>,
+				None,
+			);
+			Plain(Themed(&e, &Theme::ascii())).to_string()
+		};
+
+		// `名前` is 6 bytes, 4 columns
+		let s = render("名前 42", vec!["42".into()]);
+		assert!(s.contains("\n | 名前.42\n |      ^^\n"));
+		let s = render("日本 e\u{301} 42", vec!["42".into(), (1, 1).into()]);
+		assert!(s.contains("\n | ^^     ^^\n"));
+		// out of range, right after the line
+		let s = render("key", vec![(42, 3).into(), (usize::MAX, 2).into()]);
+		assert!(s.contains("\n |    ^\n"));
+	}
+
+	#[test]
+	fn test_snippet() {
+		use crate::parse::{Highlight, Snippet};
+
+		let e = ParseFileError::new(
+			"unterminated block",
+			"f.conf".to_string(),
+			None,
+			None::<String>,
+			None::<String>,
+			None,
+		)
+		.with_snippet(Snippet::new(
+			"a 1\nblock {\n\tb 2\n\tc 3\nd 4\ne 5",
+			vec![
+				Highlight::primary((1, 6), (3, 4), "unterminated block"),
+				Highlight::secondary((4, 0), (4, 1), "next key"),
+			],
+			1,
+		));
+
+		assert_eq!(
+			Plain(Themed(&e, &Theme::ascii())).to_string(),
+			concat!(
+				"error:\tunterminated block\n",
+				"  +-@ f.conf:1\n",
+				"  : \n",
+				"0 |   a.1\n",
+				"1 |   block.{\n",
+				"  |  _______^\n",
+				"2 | | -b.2\n",
+				"3 | | -c.3\n",
+				"  | |____^ unterminated block\n",
+				"4 |   d.4\n",
+				"  |   - next key\n",
+				"5 |   e.5\n",
+				"  : \n",
+				"  = ",
+			)
+		);
+	}
+
+	#[test]
+	fn test_suggestion() {
+		use crate::suggestion::{Edit, Suggestion};
+
+		let e = ParseFileError::new(
+			"unsupported keyword",
+			"scene.obj".to_string(),
+			Some(Line::new("tv 1 2", Some(1), vec![(0, 2).into()])),
+			None::<String>,
+			None::<String>,
+			None,
+		)
+		.with_suggestion(Suggestion::new(
+			"did you mean `vt`?",
+			vec![Edit::replace(1, 0..2, "vt"), Edit::insert(1, 6, " 3")],
+		))
+		.with_suggestion(Edit::delete(1, 2..4));
+
+		let s = Plain(Themed(&e, &Theme::ascii())).to_string();
+		assert!(s.ends_with(concat!(
+			"  : \n",
+			"  = \n",
+			"  = suggested fix: did you mean `vt`?\n",
+			"  : \n",
+			"1 | vt.1.2.3\n",
+			"  | ~~    ++\n",
+			"  : \n",
+			"  = suggested fix\n",
+			"  : \n",
+			"1 | tv.1.2\n",
+			"  |   --\n",
+			"  : ",
+		)));
+	}
+}
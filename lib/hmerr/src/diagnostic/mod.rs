@@ -0,0 +1,344 @@
+mod canvas;
+mod display;
+pub use display::render;
+mod snapshot;
+pub(crate) use snapshot::Snapshot;
+
+use crate::parse::{Line, Snippet};
+use crate::suggestion::Suggestion;
+use crate::{GenericError, IoError, ParseFileError, StandardError};
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::ops::Range;
+
+/// how bad a diagnostic is, from the least to the most severe
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+	Help,
+	Note,
+	Warning,
+	#[default]
+	Error,
+}
+
+impl Severity {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Help => "help",
+			Self::Note => "note",
+			Self::Warning => "warning",
+			Self::Error => "error",
+		}
+	}
+}
+
+/// highlighted part of the line of a diagnostic
+///
+/// primary labels point at what is wrong (`^`), secondary ones give context (`-`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+	pub range: Range<usize>, // byte range in `Line::line`
+	pub message: Option<String>,
+	pub primary: bool,
+}
+
+impl Label {
+	pub fn primary(range: Range<usize>, message: impl Into<String>) -> Self {
+		Self {
+			range,
+			message: Some(message.into()),
+			primary: true,
+		}
+	}
+
+	pub fn secondary(range: Range<usize>, message: impl Into<String>) -> Self {
+		Self {
+			range,
+			message: Some(message.into()),
+			primary: false,
+		}
+	}
+}
+
+impl From<Range<usize>> for Label {
+	fn from(range: Range<usize>) -> Self {
+		Self {
+			range,
+			message: None,
+			primary: true,
+		}
+	}
+}
+
+/// where a diagnostic happened
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Location<'a> {
+	pub file: Option<&'a str>,
+	pub line: Option<&'a Line>,
+	pub snippet: Option<&'a Snippet>, // rendered instead of `line` when set
+}
+
+/// what every hmerr error exposes, so renderers, collectors and exporters
+/// can handle any of them (or a user type) the same way
+///
+/// only `message` is required, a user type can get the hmerr layout
+/// by calling `diagnostic::render` in its `Display`:
+///
+/// ```
+/// use std::borrow::Cow;
+/// use std::error::Error;
+/// use std::fmt::{Debug, Display, Formatter};
+///
+/// use hmerr::diagnostic::{self, Diagnostic, Severity};
+/// use hmerr::display::Plain;
+///
+/// struct Deprecated(&'static str);
+///
+/// impl Diagnostic for Deprecated {
+///     fn severity(&self) -> Severity {
+///         Severity::Warning
+///     }
+///
+///     fn message(&self) -> Cow<'_, str> {
+///         format!("`{}` is deprecated", self.0).into()
+///     }
+/// }
+///
+/// impl Display for Deprecated {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+///         diagnostic::render(f, self)
+///     }
+/// }
+///
+/// impl Debug for Deprecated {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+///         Display::fmt(self, f)
+///     }
+/// }
+///
+/// impl Error for Deprecated {}
+///
+/// assert_eq!(Plain(Deprecated("vt")).to_string(), "warning:\t`vt` is deprecated");
+/// ```
+pub trait Diagnostic: Error {
+	fn severity(&self) -> Severity {
+		Severity::Error
+	}
+
+	fn message(&self) -> Cow<'_, str>;
+
+	/// stable code shown in the header, see `explain`
+	fn code(&self) -> Option<&str> {
+		None
+	}
+
+	fn help(&self) -> Option<&str> {
+		None
+	}
+
+	fn location(&self) -> Option<Location<'_>> {
+		None
+	}
+
+	/// highlighted parts of the line of `location`, by default `Line::labels`
+	fn labels(&self) -> Vec<Label> {
+		self.location()
+			.and_then(|l| l.line)
+			.map(Line::labels)
+			.unwrap_or_default()
+	}
+
+	/// changes to the source that would fix the diagnostic
+	fn suggestions(&self) -> &[Suggestion] {
+		&[]
+	}
+
+	/// rust file that generated the diagnostic
+	fn source_file(&self) -> Option<&str> {
+		None
+	}
+
+	fn caused_by(&self) -> Option<&(dyn Error + 'static)> {
+		self.source()
+	}
+}
+
+/// `e` as a `Diagnostic` if it is one of the hmerr errors
+pub fn as_diagnostic<'a>(e: &'a (dyn Error + 'static)) -> Option<&'a dyn Diagnostic> {
+	if let Some(e) = e.downcast_ref::<ParseFileError>() {
+		Some(e)
+	} else if let Some(e) = e.downcast_ref::<StandardError>() {
+		Some(e)
+	} else if let Some(e) = e.downcast_ref::<IoError>() {
+		Some(e)
+	} else if let Some(e) = e.downcast_ref::<GenericError>() {
+		Some(e)
+	} else if let Some(e) = e.downcast_ref::<crate::report::Annotated>() {
+		Some(e)
+	} else if let Some(e) = e.downcast_ref::<crate::report::DynDiagnostic>() {
+		Some(e)
+	} else {
+		None
+	}
+}
+
+/// `self` as a `dyn Error`, for an error or a boxed one (used by `#[derive(HmError)]`)
+pub trait AsDynError {
+	fn as_dyn_error(&self) -> &(dyn Error + 'static);
+}
+
+impl<E: Error + 'static> AsDynError for E {
+	fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+		self
+	}
+}
+
+impl AsDynError for dyn Error + 'static {
+	fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+		self
+	}
+}
+
+impl AsDynError for dyn Error + Send + Sync + 'static {
+	fn as_dyn_error(&self) -> &(dyn Error + 'static) {
+		self
+	}
+}
+
+impl Diagnostic for ParseFileError {
+	fn message(&self) -> Cow<'_, str> {
+		Cow::Borrowed(&self.error)
+	}
+
+	fn code(&self) -> Option<&str> {
+		self.code.as_deref()
+	}
+
+	fn help(&self) -> Option<&str> {
+		self.help.as_deref()
+	}
+
+	fn location(&self) -> Option<Location<'_>> {
+		if self.file.is_none() && self.line.is_none() && self.snippet.is_none() {
+			return None;
+		}
+
+		Some(Location {
+			file: self.file.as_deref(),
+			line: self.line.as_ref(),
+			snippet: self.snippet.as_ref(),
+		})
+	}
+
+	fn suggestions(&self) -> &[Suggestion] {
+		&self.suggestion
+	}
+
+	fn source_file(&self) -> Option<&str> {
+		self.source_file.as_deref()
+	}
+}
+
+impl Diagnostic for StandardError {
+	fn message(&self) -> Cow<'_, str> {
+		Cow::Borrowed(&self.error)
+	}
+
+	fn help(&self) -> Option<&str> {
+		self.help.as_deref()
+	}
+
+	fn source_file(&self) -> Option<&str> {
+		self.source_file.as_deref()
+	}
+}
+
+impl Diagnostic for IoError {
+	fn message(&self) -> Cow<'_, str> {
+		Cow::Owned(crate::io::display_error(&self.error, &self.file))
+	}
+
+	fn help(&self) -> Option<&str> {
+		self.help
+			.as_deref()
+			.or_else(|| crate::io::help(&self.error))
+	}
+
+	fn location(&self) -> Option<Location<'_>> {
+		Some(Location {
+			file: Some(&self.file),
+			..Default::default()
+		})
+	}
+
+	fn source_file(&self) -> Option<&str> {
+		self.source_file.as_deref()
+	}
+}
+
+impl Diagnostic for GenericError {
+	fn message(&self) -> Cow<'_, str> {
+		Cow::Borrowed(&self.error)
+	}
+
+	fn help(&self) -> Option<&str> {
+		self.help.as_deref()
+	}
+
+	fn source_file(&self) -> Option<&str> {
+		self.source_file.as_deref()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::parse::Line;
+
+	#[test]
+	fn test_diagnostic_parse() {
+		let e = ParseFileError::new(
+			"bad value",
+			"f.conf".to_string(),
+			Some(Line::new("key val val", Some(3), vec!["val".into()])),
+			"help".to_string(),
+			None::<String>,
+			Some(Box::new(std::fmt::Error)),
+		);
+		let d: &dyn Diagnostic = &e;
+
+		assert_eq!(d.severity(), Severity::Error);
+		assert_eq!(d.message(), "bad value");
+		assert_eq!(d.help(), Some("help"));
+		let location = d.location().unwrap();
+		assert_eq!(location.file, Some("f.conf"));
+		assert_eq!(location.line.unwrap().index, Some(3));
+		assert_eq!(d.labels(), vec![Label::from(4..7), Label::from(8..11)]);
+		assert!(d.caused_by().unwrap().is::<std::fmt::Error>());
+	}
+
+	#[test]
+	fn test_as_diagnostic() {
+		let e: Box<dyn Error> = Box::new(IoError {
+			file: String::from("f.conf"),
+			error: std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+			help: None,
+			source_file: None,
+			source: None,
+		});
+
+		let d = as_diagnostic(&*e).unwrap();
+		assert_eq!(d.location().unwrap().file, Some("f.conf"));
+		assert!(d.labels().is_empty());
+		assert!(as_diagnostic(&std::fmt::Error).is_none());
+	}
+
+	#[test]
+	fn test_severity_order() {
+		assert!(Severity::Error > Severity::Warning);
+		assert!(Severity::Warning > Severity::Note);
+		assert!(Severity::Note > Severity::Help);
+		assert_eq!(Severity::default(), Severity::Error);
+	}
+}
@@ -0,0 +1,174 @@
+use super::{Diagnostic, Label, Location, Severity, as_diagnostic};
+
+use crate::parse::{Line, Snippet};
+use crate::suggestion::Suggestion;
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// owned copy of what a `Diagnostic` exposes, but its cause
+///
+/// lets an error only known as `dyn Error` be rendered with its own message,
+/// code and location, rather than with its (already rendered) `Display`
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshot {
+	pub severity: Severity,
+	pub message: String,
+	pub code: Option<String>,
+	pub help: Option<String>,
+	pub file: Option<String>,
+	pub line: Option<Line>,
+	pub snippet: Option<Snippet>,
+	pub labels: Vec<Label>,
+	pub suggestions: Vec<Suggestion>,
+	pub source_file: Option<String>,
+}
+
+/// state of `Snapshot::of` on this thread
+enum Capture {
+	Off,
+	// formatting the error, no `render` yet
+	Waiting,
+	// rendering the first diagnostic, its causes are not captured
+	Rendering,
+	// first diagnostic, and how it rendered
+	Done(Box<Snapshot>, String),
+}
+
+thread_local! {
+	static CAPTURE: RefCell<Capture> = const { RefCell::new(Capture::Off) };
+}
+
+impl Snapshot {
+	pub fn new(d: &dyn Diagnostic) -> Self {
+		let location = d.location().unwrap_or_default();
+		Self {
+			severity: d.severity(),
+			message: d.message().into_owned(),
+			code: d.code().map(String::from),
+			help: d.help().map(String::from),
+			file: location.file.map(String::from),
+			line: location.line.cloned(),
+			snippet: location.snippet.cloned(),
+			labels: d.labels(),
+			suggestions: d.suggestions().to_vec(),
+			source_file: d.source_file().map(String::from),
+		}
+	}
+
+	/// `e` as a `Diagnostic`, if it is one of the hmerr errors
+	/// or if its `Display` is only a call to `diagnostic::render` (like `#[derive(HmError)]` does)
+	///
+	/// `None` if `e` writes anything else, a wrapper like `"loading failed: {inner}"`
+	/// must keep its own text
+	pub fn of(e: &(dyn Error + 'static)) -> Option<Self> {
+		if let Some(d) = as_diagnostic(e) {
+			return Some(Self::new(d));
+		}
+
+		struct Reset;
+		impl Drop for Reset {
+			fn drop(&mut self) {
+				CAPTURE.with(|c| *c.borrow_mut() = Capture::Off);
+			}
+		}
+
+		let _reset = Reset;
+		CAPTURE.with(|c| *c.borrow_mut() = Capture::Waiting);
+		let mut text = String::new();
+		std::fmt::write(&mut text, format_args!("{e}")).ok()?;
+		match CAPTURE.with(|c| c.replace(Capture::Off)) {
+			Capture::Done(snapshot, rendered) if rendered == text => Some(*snapshot),
+			_ => None,
+		}
+	}
+
+	pub fn location(&self) -> Option<Location<'_>> {
+		if self.file.is_none() && self.line.is_none() && self.snippet.is_none() {
+			return None;
+		}
+
+		Some(Location {
+			file: self.file.as_deref(),
+			line: self.line.as_ref(),
+			snippet: self.snippet.as_ref(),
+		})
+	}
+}
+
+/// called by `render`, `Some` if `d` was captured by `Snapshot::of` and rendered
+pub(crate) fn capture(f: &mut Formatter<'_>, d: &dyn Diagnostic) -> Option<std::fmt::Result> {
+	if !CAPTURE.with(|c| matches!(*c.borrow(), Capture::Waiting)) {
+		return None;
+	}
+
+	// outside of the borrow, `d` may format itself
+	CAPTURE.with(|c| *c.borrow_mut() = Capture::Rendering);
+	let snapshot = Snapshot::new(d);
+	let rendered = Rendered(d).to_string();
+	let result = f.write_str(&rendered);
+	CAPTURE.with(|c| *c.borrow_mut() = Capture::Done(Box::new(snapshot), rendered));
+	Some(result)
+}
+
+struct Rendered<'a>(&'a dyn Diagnostic);
+
+impl Display for Rendered<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		super::render(f, self.0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::borrow::Cow;
+
+	#[derive(Debug)]
+	struct Missing;
+
+	impl Diagnostic for Missing {
+		fn message(&self) -> Cow<'_, str> {
+			Cow::Borrowed("missing `port`")
+		}
+
+		fn code(&self) -> Option<&str> {
+			Some("E0001")
+		}
+	}
+
+	impl Display for Missing {
+		fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+			crate::diagnostic::render(f, self)
+		}
+	}
+
+	impl Error for Missing {}
+
+	/// its own text around a rendered error
+	#[derive(Debug)]
+	struct Wrapper(Missing);
+
+	impl Display for Wrapper {
+		fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+			write!(f, "loading config failed: {}", self.0)
+		}
+	}
+
+	impl Error for Wrapper {}
+
+	#[test]
+	fn test_snapshot_of() {
+		let snapshot = Snapshot::of(&Missing).unwrap();
+		assert_eq!(snapshot.message, "missing `port`");
+		assert_eq!(snapshot.code.as_deref(), Some("E0001"));
+
+		assert!(Snapshot::of(&Wrapper(Missing)).is_none());
+		assert!(Snapshot::of(&std::fmt::Error).is_none());
+		// nothing left captured
+		assert!(Wrapper(Missing).to_string().contains("missing `port`"));
+		assert!(Snapshot::of(&Missing).is_some());
+	}
+}
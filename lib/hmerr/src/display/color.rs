@@ -0,0 +1,187 @@
+use std::ffi::OsStr;
+use std::fmt::{Display, Formatter};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// when should errors be rendered with ANSI escapes
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum ColorChoice {
+	#[default]
+	Auto, // `NO_COLOR`, then `CLICOLOR_FORCE`, then whether stderr is a terminal
+	Always,
+	Never,
+}
+
+static CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// select how every error is rendered from now on
+pub fn set_color(choice: ColorChoice) {
+	CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+pub fn color() -> ColorChoice {
+	match CHOICE.load(Ordering::Relaxed) {
+		1 => ColorChoice::Always,
+		2 => ColorChoice::Never,
+		_ => ColorChoice::Auto,
+	}
+}
+
+/// whether errors are currently rendered with ANSI escapes
+///
+/// with `ColorChoice::Auto`:
+/// * `NO_COLOR` set and not empty - no color (<https://no-color.org>)
+/// * `CLICOLOR_FORCE` set and not `0` - color
+/// * otherwise, color only if stderr is a terminal
+pub fn color_enabled() -> bool {
+	enabled(
+		color(),
+		std::env::var_os("NO_COLOR").as_deref(),
+		std::env::var_os("CLICOLOR_FORCE").as_deref(),
+		|| std::io::stderr().is_terminal(),
+	)
+}
+
+/// `color_enabled` from the values of `NO_COLOR`, `CLICOLOR_FORCE` and whether stderr is a terminal
+fn enabled(
+	choice: ColorChoice,
+	no_color: Option<&OsStr>,
+	clicolor_force: Option<&OsStr>,
+	tty: impl FnOnce() -> bool,
+) -> bool {
+	match choice {
+		ColorChoice::Always => true,
+		ColorChoice::Never => false,
+		ColorChoice::Auto => {
+			if no_color.is_some_and(|v| !v.is_empty()) {
+				false
+			} else if clicolor_force.is_some_and(|v| !v.is_empty() && v != "0") {
+				true
+			} else {
+				tty()
+			}
+		}
+	}
+}
+
+/// remove every ANSI escape sequence of `s`
+pub fn strip(s: &str) -> String {
+	let mut out = String::with_capacity(s.len());
+	let mut c = s.chars().peekable();
+
+	while let Some(x) = c.next() {
+		if x != '\x1b' {
+			out.push(x);
+			continue;
+		}
+
+		// `ESC [ parameters final`, final byte in `@..=~`
+		if c.next_if_eq(&'[').is_some() {
+			for x in c.by_ref() {
+				if ('@'..='~').contains(&x) {
+					break;
+				}
+			}
+		} else {
+			c.next();
+		}
+	}
+
+	out
+}
+
+/// `s` as is if colors are enabled, stripped of ANSI escapes otherwise
+pub fn maybe_strip(s: &str) -> String {
+	if color_enabled() {
+		s.to_string()
+	} else {
+		strip(s)
+	}
+}
+
+/// render `T` without any ANSI escape, whatever `ColorChoice` is selected
+///
+/// the layout (file sign, side bar, carets, help) stays the same
+///
+/// ```
+/// use hmerr::display::Plain;
+/// use hmerr::ge;
+///
+/// let e = ge!("oops", h:"try again");
+/// assert!(!Plain(&e).to_string().contains('\x1b'));
+/// assert!(Plain(&e).to_string().contains("help: try again"));
+/// ```
+pub struct Plain<T>(pub T);
+
+impl<T: Display> Display for Plain<T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&strip(&self.0.to_string()))
+	}
+}
+
+/// render with `body` as is if colors are enabled, stripped of ANSI escapes otherwise
+pub(crate) fn render<F>(f: &mut Formatter<'_>, body: F) -> std::fmt::Result
+where
+	F: Fn(&mut Formatter<'_>) -> std::fmt::Result,
+{
+	if color_enabled() {
+		body(f)
+	} else {
+		write!(f, "{}", Plain(Body(body)))
+	}
+}
+
+/// prefix of `Debug`, clear the current line of the terminal
+pub(crate) fn clear_line() -> &'static str {
+	if color_enabled() { "\x1b[2K\r" } else { "" }
+}
+
+struct Body<F>(F);
+
+impl<F> Display for Body<F>
+where
+	F: Fn(&mut Formatter<'_>) -> std::fmt::Result,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		(self.0)(f)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_strip() {
+		assert_eq!(strip(""), "");
+		assert_eq!(strip("plain"), "plain");
+		assert_eq!(
+			strip("\x1b[0m\x1b[1;31merror\x1b[39m:\x1b[0m\t"),
+			"error:\t"
+		);
+		assert_eq!(strip("\x1b[38;2;161;211;255mfile╭╴◊"), "file╭╴◊");
+		assert_eq!(strip("\x1b[2K\rline"), "\rline");
+		assert_eq!(strip("end\x1b["), "end");
+	}
+
+	#[test]
+	fn test_color_choice() {
+		let set = Some(OsStr::new("1"));
+		let empty = Some(OsStr::new(""));
+		let zero = Some(OsStr::new("0"));
+
+		assert!(enabled(ColorChoice::Always, set, None, || false));
+		assert!(!enabled(ColorChoice::Never, None, set, || true));
+
+		let auto = |no_color, clicolor_force, tty| {
+			enabled(ColorChoice::Auto, no_color, clicolor_force, || tty)
+		};
+		assert!(!auto(set, set, true));
+		assert!(auto(empty, set, false));
+		assert!(!auto(None, zero, false));
+		assert!(!auto(None, empty, false));
+		assert!(auto(None, None, true));
+		assert!(!auto(None, None, false));
+	}
+}
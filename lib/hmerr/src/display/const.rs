@@ -1,3 +1,7 @@
+//! rendering of `Theme::dark`, kept for compatibility
+//!
+//! errors are rendered with the selected `theme()`
+
 pub const ERROR: &str = "\x1b[0m\x1b[1;31merror\x1b[39m:\x1b[0m\t";
 pub const WARNING: &str = "\x1b[0m\x1b[1;33mwarning\x1b[39m:\x1b[0m\t";
 pub const HELP: &str = "\x1b[0m\x1b[36mhelp\x1b[0m\x1b[1m:\x1b[0m ";
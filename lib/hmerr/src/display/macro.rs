@@ -1,27 +1,27 @@
 #[macro_export]
 macro_rules! err {
 	($($arg:tt)*) => {
-		eprintln!("{}{}", $crate::display::ERROR, format!($($arg)*));
+		eprintln!("{}", $crate::display::maybe_strip(&format!("{}{}", $crate::display::theme().error_label(), format!($($arg)*))));
 	};
 }
 
 #[macro_export]
 macro_rules! errnl {
 	($($arg:tt)*) => {
-		eprint!("{}{}", $crate::display::ERROR, format!($($arg)*));
+		eprint!("{}", $crate::display::maybe_strip(&format!("{}{}", $crate::display::theme().error_label(), format!($($arg)*))));
 	};
 }
 
 #[macro_export]
 macro_rules! warn {
 	($($arg:tt)*) => {
-		eprintln!("{}{}", $crate::display::WARNING, format!($($arg)*));
+		eprintln!("{}", $crate::display::maybe_strip(&format!("{}{}", $crate::display::theme().warning_label(), format!($($arg)*))));
 	};
 }
 
 #[macro_export]
 macro_rules! warnnl {
 	($($arg:tt)*) => {
-		eprint!("{}{}", $crate::display::WARNING, format!($($arg)*));
+		eprint!("{}", $crate::display::maybe_strip(&format!("{}{}", $crate::display::theme().warning_label(), format!($($arg)*))));
 	};
 }
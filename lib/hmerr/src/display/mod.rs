@@ -3,4 +3,14 @@ pub use r#const::*;
 
 mod r#macro;
 
+mod color;
+pub use color::{ColorChoice, Plain, color, color_enabled, maybe_strip, set_color, strip};
+pub(crate) use color::{clear_line, render};
+
+mod theme;
+pub use theme::{RESET, Theme, Themed, set_theme, theme};
+
+mod width;
+pub use width::{char_width, width};
+
 pub mod write;
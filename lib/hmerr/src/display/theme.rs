@@ -0,0 +1,286 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, RwLock};
+
+use crate::diagnostic::Severity;
+
+pub const RESET: &str = "\x1b[0m";
+
+type Str = Cow<'static, str>;
+
+/// every color (ANSI escape sequence, empty for none) and glyph used to render an error
+///
+/// start from a preset and override what is needed:
+///
+/// ```
+/// use hmerr::display::{Theme, set_theme};
+///
+/// set_theme(Theme {
+///     error: "\x1b[1;38;2;255;120;0m".into(),
+///     side: "\x1b[1;38;2;255;120;0m".into(),
+///     ..Theme::ascii()
+/// });
+/// # set_theme(Theme::dark());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+	pub error: Str,
+	pub warning: Str,
+	pub note: Str,
+	pub help: Str,
+	pub side: Str,   // side bar, signs and line number
+	pub source: Str, // side bar of a caused by error
+	pub file: Str,
+	pub lint: Str,
+	pub expected: Str,
+	pub got: Str,
+	pub label: Str, // `expected:`, `got:`, `nothing`
+	pub source_file: Str,
+	pub tab: Str,
+	pub space: Str,
+	pub bold: Str,    // `:` of `help:`, file of `generated in:`
+	pub default: Str, // `:` of `error:`, still bold but not colored
+
+	pub file_sign: Str,
+	pub side_padding_sign: Str,
+	pub side_sign: Str,
+	pub help_sign: Str,
+	pub source_sign: Str, // `caused by` banner
+	pub source_side_sign: Str,
+	pub lint_sign: char,
+	pub secondary_sign: char, // underline of a secondary label
+	pub tab_sign: Str,
+	pub space_sign: Str,
+}
+
+impl Default for Theme {
+	fn default() -> Self {
+		Self::dark()
+	}
+}
+
+impl Theme {
+	/// colors for a dark background, box drawing characters
+	pub fn dark() -> Self {
+		Self {
+			error: Cow::Borrowed("\x1b[1;31m"),
+			warning: Cow::Borrowed("\x1b[1;33m"),
+			note: Cow::Borrowed("\x1b[1;32m"),
+			help: Cow::Borrowed("\x1b[36m"),
+			side: Cow::Borrowed("\x1b[1;34m"),
+			source: Cow::Borrowed("\x1b[1;31m"),
+			file: Cow::Borrowed("\x1b[38;2;161;211;255m"),
+			lint: Cow::Borrowed("\x1b[1;38;2;255;0;64m"),
+			expected: Cow::Borrowed("\x1b[1;32m"),
+			got: Cow::Borrowed("\x1b[1;31m"),
+			label: Cow::Borrowed("\x1b[2m"),
+			source_file: Cow::Borrowed("\x1b[2;3m"),
+			tab: Cow::Borrowed("\x1b[2;35m"),
+			space: Cow::Borrowed("\x1b[2;36m"),
+			bold: Cow::Borrowed("\x1b[1m"),
+			default: Cow::Borrowed("\x1b[39m"),
+
+			file_sign: Cow::Borrowed("╭╴◊"),
+			side_padding_sign: Cow::Borrowed("┆"),
+			side_sign: Cow::Borrowed("│"),
+			help_sign: Cow::Borrowed("╧"),
+			source_sign: Cow::Borrowed("╰╴caused by╶╮"),
+			source_side_sign: Cow::Borrowed("│"),
+			lint_sign: '^',
+			secondary_sign: '-',
+			tab_sign: Cow::Borrowed("-"),
+			space_sign: Cow::Borrowed("·"),
+		}
+	}
+
+	/// darker colors readable on a light background
+	pub fn light() -> Self {
+		Self {
+			error: Cow::Borrowed("\x1b[1;38;2;190;0;0m"),
+			warning: Cow::Borrowed("\x1b[1;38;2;170;100;0m"),
+			note: Cow::Borrowed("\x1b[1;38;2;0;130;0m"),
+			help: Cow::Borrowed("\x1b[38;2;0;120;140m"),
+			side: Cow::Borrowed("\x1b[1;38;2;0;60;170m"),
+			source: Cow::Borrowed("\x1b[1;38;2;190;0;0m"),
+			file: Cow::Borrowed("\x1b[38;2;0;90;170m"),
+			lint: Cow::Borrowed("\x1b[1;38;2;210;0;50m"),
+			expected: Cow::Borrowed("\x1b[1;38;2;0;130;0m"),
+			got: Cow::Borrowed("\x1b[1;38;2;190;0;0m"),
+			label: Cow::Borrowed("\x1b[38;2;100;100;100m"),
+			source_file: Cow::Borrowed("\x1b[3;38;2;100;100;100m"),
+			tab: Cow::Borrowed("\x1b[38;2;160;0;160m"),
+			space: Cow::Borrowed("\x1b[38;2;0;140;160m"),
+			..Self::dark()
+		}
+	}
+
+	/// colors of `dark`, ASCII only glyphs for limited terminals
+	pub fn ascii() -> Self {
+		Self {
+			file_sign: Cow::Borrowed("+-@"),
+			side_padding_sign: Cow::Borrowed(":"),
+			side_sign: Cow::Borrowed("|"),
+			help_sign: Cow::Borrowed("="),
+			source_sign: Cow::Borrowed("`- caused by -."),
+			source_side_sign: Cow::Borrowed("|"),
+			lint_sign: '^',
+			secondary_sign: '-',
+			tab_sign: Cow::Borrowed("-"),
+			space_sign: Cow::Borrowed("."),
+			..Self::dark()
+		}
+	}
+
+	/// `error:` label, followed by a tab
+	pub fn error_label(&self) -> String {
+		self.severity_label(Severity::Error)
+	}
+
+	/// `warning:` label, followed by a tab
+	pub fn warning_label(&self) -> String {
+		self.severity_label(Severity::Warning)
+	}
+
+	/// `error:`, `warning:`, `note:` or `help:` label, followed by a tab
+	pub fn severity_label(&self, severity: Severity) -> String {
+		self.code_label(severity, None)
+	}
+
+	/// `severity_label` with the code of the error, `error[SPOF0003]:`
+	pub fn code_label(&self, severity: Severity, code: Option<&str>) -> String {
+		let c = match severity {
+			Severity::Error => &self.error,
+			Severity::Warning => &self.warning,
+			Severity::Note => &self.note,
+			Severity::Help => &self.help,
+		};
+		let code = code.map(|code| format!("[{code}]")).unwrap_or_default();
+		format!(
+			"{RESET}{c}{s}{code}{d}:{RESET}\t",
+			s = severity.as_str(),
+			d = self.default
+		)
+	}
+
+	pub(crate) fn help_label(&self) -> String {
+		format!(
+			"{RESET}{c}help{RESET}{b}:{RESET} ",
+			c = self.help,
+			b = self.bold
+		)
+	}
+
+	/// `sign` colored as the side bar, followed by a space
+	pub(crate) fn sign(&self, sign: &str) -> String {
+		format!("{RESET}{c}{sign}{RESET} ", c = self.side)
+	}
+
+	pub(crate) fn source_side(&self) -> String {
+		format!(
+			"{RESET}{c}{s}{RESET} ",
+			c = self.source,
+			s = self.source_side_sign
+		)
+	}
+}
+
+static THEME: RwLock<Option<Arc<Theme>>> = RwLock::new(None);
+
+thread_local! {
+	static LOCAL: RefCell<Option<Arc<Theme>>> = const { RefCell::new(None) };
+}
+
+/// select the theme of every error rendered from now on (default: `Theme::dark`)
+pub fn set_theme(theme: Theme) {
+	*THEME.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(theme));
+}
+
+/// theme of the current render
+pub fn theme() -> Arc<Theme> {
+	if let Some(t) = LOCAL.with(|l| l.borrow().clone()) {
+		return t;
+	}
+
+	THEME
+		.read()
+		.unwrap_or_else(|e| e.into_inner())
+		.clone()
+		.unwrap_or_else(|| Arc::new(Theme::dark()))
+}
+
+/// render `T` with `theme`, whatever theme is selected globally
+///
+/// ```
+/// use hmerr::display::{Theme, Themed};
+/// use hmerr::ge;
+///
+/// let e = ge!("oops", h:"try again");
+/// let ascii = Themed(&e, &Theme::ascii()).to_string();
+/// assert!(ascii.contains('='));
+/// assert!(!ascii.contains('╧'));
+/// ```
+pub struct Themed<'a, T>(pub T, pub &'a Theme);
+
+impl<T: Display> Display for Themed<'_, T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let previous = LOCAL.with(|l| l.replace(Some(Arc::new(self.1.clone()))));
+		let r = write!(f, "{}", self.0);
+		LOCAL.with(|l| *l.borrow_mut() = previous);
+		r
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::display::strip;
+
+	#[test]
+	fn test_theme_dark_matches_const() {
+		use crate::display::*;
+
+		let t = Theme::dark();
+		assert_eq!(t.error_label(), ERROR);
+		assert_eq!(t.warning_label(), WARNING);
+		assert_eq!(t.help_label(), HELP);
+		assert_eq!(t.sign(&t.file_sign), FILE_SIGN);
+		assert_eq!(t.sign(&t.side_padding_sign), SIDE_PADDING_SIGN);
+		assert_eq!(t.sign(&t.side_sign), SIDE_SIGN);
+		assert_eq!(t.sign(&t.help_sign), HELP_SIGN);
+		assert_eq!(t.source_side(), SOURCE_SIDE_SIGN);
+
+		// no escape left outside of the theme
+		let bare = Theme {
+			error: Cow::Borrowed(""),
+			help: Cow::Borrowed(""),
+			bold: Cow::Borrowed(""),
+			default: Cow::Borrowed(""),
+			..Theme::dark()
+		};
+		assert_eq!(
+			bare.code_label(Severity::Error, Some("E1")),
+			format!("{RESET}error[E1]:{RESET}\t")
+		);
+		assert_eq!(bare.help_label(), format!("{RESET}help{RESET}:{RESET} "));
+	}
+
+	#[test]
+	fn test_themed() {
+		let e = crate::GenericError {
+			error: String::from("oops"),
+			help: Some(String::from("try again")),
+			source_file: None,
+			source: None,
+		};
+		let custom = Theme {
+			help_sign: Cow::Borrowed("HELP>"),
+			..Theme::ascii()
+		};
+
+		let s = strip(&Themed(&e, &custom).to_string());
+		assert!(s.contains("HELP> help: try again"));
+		// the override only lasts for the render
+		assert!(!strip(&Themed(&e, &Theme::dark()).to_string()).contains("HELP>"));
+	}
+}
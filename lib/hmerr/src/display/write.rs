@@ -1,18 +1,39 @@
 use std::fmt::Display;
 
-use crate::display::{
-	ERROR, FILE_COLOR, FILE_SIGN, FILE_SOURCE, HELP, HELP_SIGN, SIDE, SOURCE, SOURCE_SIDE_SIGN,
-};
+use crate::diagnostic::Severity;
+use crate::display::{RESET, theme};
 
 pub fn error(f: &mut std::fmt::Formatter<'_>, error: &impl Display) -> std::fmt::Result {
-	write!(f, "{ERROR}{error}")
+	severity(f, Severity::Error, error)
+}
+
+pub fn severity(
+	f: &mut std::fmt::Formatter<'_>,
+	severity: Severity,
+	message: &impl Display,
+) -> std::fmt::Result {
+	code(f, severity, None, message)
+}
+
+/// header with the code of the error, `error[SPOF0003]:`
+pub fn code(
+	f: &mut std::fmt::Formatter<'_>,
+	severity: Severity,
+	code: Option<&str>,
+	message: &impl Display,
+) -> std::fmt::Result {
+	write!(
+		f,
+		"{label}{message}",
+		label = theme().code_label(severity, code)
+	)
 }
 
 pub fn padding<E>(source: Option<&E>, n: Option<usize>) -> String {
 	let padding = n_to_padding(n);
 
 	if source.is_some() {
-		return SOURCE_SIDE_SIGN.to_string() + &padding;
+		return theme().source_side() + &padding;
 	}
 
 	padding
@@ -32,17 +53,27 @@ pub fn file(
 	file: Option<&str>,
 	index: Option<usize>,
 ) -> std::fmt::Result {
+	let t = theme();
+	let file_sign = t.sign(&t.file_sign);
 	let Some(file) = file else {
-		return write!(f, "\n{padding}{FILE_SIGN}");
+		return write!(f, "\n{padding}{file_sign}");
 	};
 
 	let index: String = if let Some(index) = index {
-		format!("\x1b[0m{SIDE}:\x1b[0m{FILE_COLOR}{index}")
+		format!(
+			"{RESET}{RESET}{side}:{RESET}{RESET}{color}{index}",
+			side = t.side,
+			color = t.file
+		)
 	} else {
 		String::new()
 	};
 
-	write!(f, "\n{padding}{FILE_SIGN}{FILE_COLOR}{file}{index}\x1b[0m")
+	write!(
+		f,
+		"\n{padding}{file_sign}{RESET}{color}{file}{index}{RESET}",
+		color = t.file
+	)
 }
 
 pub fn help(
@@ -50,14 +81,17 @@ pub fn help(
 	padding: &str,
 	help: Option<&str>,
 ) -> std::fmt::Result {
+	let t = theme();
+	let help_sign = t.sign(&t.help_sign);
 	if let Some(help) = help {
 		write!(
 			f,
-			"\n{padding}{HELP_SIGN}{HELP}{help}",
+			"\n{padding}{help_sign}{label}{help}",
+			label = t.help_label(),
 			help = help.replace('\n', format!("\n{padding}\t").as_str())
 		)
 	} else {
-		write!(f, "\n{padding}{HELP_SIGN}")
+		write!(f, "\n{padding}{help_sign}")
 	}
 }
 
@@ -67,7 +101,13 @@ pub fn source_file(
 	source_file: Option<&str>,
 ) -> std::fmt::Result {
 	if let Some(source_file) = source_file {
-		write!(f, "\n{padding}{FILE_SOURCE}{source_file}\x1b[0m")
+		let t = theme();
+		write!(
+			f,
+			"\n{padding}{RESET}{color}generated in: {b}{source_file}{RESET}",
+			color = t.source_file,
+			b = t.bold
+		)
 	} else {
 		Ok(())
 	}
@@ -75,7 +115,13 @@ pub fn source_file(
 
 pub fn source(f: &mut std::fmt::Formatter<'_>, source: Option<&impl Display>) -> std::fmt::Result {
 	if let Some(source) = source {
-		write!(f, "\n{SOURCE}{source}\x1b[0m")
+		let t = theme();
+		write!(
+			f,
+			"\n{RESET}{color}{sign}{RESET}\n{source}{RESET}",
+			color = t.source,
+			sign = t.source_sign
+		)
 	} else {
 		Ok(())
 	}
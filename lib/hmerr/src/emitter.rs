@@ -0,0 +1,394 @@
+//! collect diagnostics of every severity, print them and a final summary
+//!
+//! unlike `err!` and `warn!`, an `Emitter` counts what it prints,
+//! can turn warnings into errors, skip repeats, and stop printing after a limit
+
+use crate::diagnostic::{self, Diagnostic, Label, Location, Severity, as_diagnostic};
+use crate::display::{self, strip, theme};
+use crate::report::Foreign;
+use crate::suggestion::Suggestion;
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::io::{self, Stderr, Write};
+
+/// prints diagnostics to `W` (stderr by default) and counts them by severity
+///
+/// ```
+/// use hmerr::emitter::Emitter;
+/// use hmerr::display::strip;
+///
+/// let mut emitter = Emitter::with_writer(Vec::new());
+/// emitter.warning("`vt` is deprecated").unwrap();
+/// emitter.warning("`vt` is deprecated").unwrap(); // repeat, not printed nor counted
+/// emitter.error("missing `v`").unwrap();
+/// emitter.summary().unwrap();
+///
+/// assert_eq!(emitter.count(hmerr::Severity::Warning), 1);
+/// let out = strip(&String::from_utf8(emitter.into_writer()).unwrap());
+/// assert!(out.ends_with("error:\t1 error, 1 warning emitted\n"));
+/// ```
+pub struct Emitter<W: Write = Stderr> {
+	writer: W,
+	warnings_as_errors: bool,
+	dedup: bool,
+	limit: Option<usize>,
+	seen: BTreeSet<String>,
+	count: [usize; 4], // by `Severity`
+	shown: usize,
+	hidden: usize,
+}
+
+impl Emitter {
+	/// print to stderr
+	pub fn new() -> Self {
+		Self::with_writer(io::stderr())
+	}
+}
+
+impl Default for Emitter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<W: Write> Emitter<W> {
+	pub fn with_writer(writer: W) -> Self {
+		Self {
+			writer,
+			warnings_as_errors: false,
+			dedup: true,
+			limit: None,
+			seen: BTreeSet::new(),
+			count: [0; 4],
+			shown: 0,
+			hidden: 0,
+		}
+	}
+
+	/// print and count every warning as an error
+	pub fn warnings_as_errors(mut self, yes: bool) -> Self {
+		self.warnings_as_errors = yes;
+		self
+	}
+
+	/// skip a diagnostic rendered exactly like one already emitted (on by default)
+	pub fn dedup(mut self, yes: bool) -> Self {
+		self.dedup = yes;
+		self
+	}
+
+	/// print at most `limit` diagnostics, the others are still counted
+	pub fn limit(mut self, limit: usize) -> Self {
+		self.limit = Some(limit);
+		self
+	}
+
+	pub fn emit(&mut self, d: &dyn Diagnostic) -> io::Result<()> {
+		if self.warnings_as_errors && d.severity() == Severity::Warning {
+			return self.write(Severity::Error, &Promoted(d));
+		}
+		self.write(d.severity(), d)
+	}
+
+	/// emit any error, as a `Diagnostic` if it is one of the hmerr errors
+	/// or if it renders itself with `diagnostic::render` (like `#[derive(HmError)]`)
+	pub fn emit_error(&mut self, e: &(dyn Error + 'static)) -> io::Result<()> {
+		match as_diagnostic(e) {
+			Some(d) => self.emit(d),
+			None => self.emit(&Foreign::new(e)),
+		}
+	}
+
+	pub fn error(&mut self, message: impl Into<String>) -> io::Result<()> {
+		self.emit(&Message(Severity::Error, message.into()))
+	}
+
+	pub fn warning(&mut self, message: impl Into<String>) -> io::Result<()> {
+		self.emit(&Message(Severity::Warning, message.into()))
+	}
+
+	pub fn note(&mut self, message: impl Into<String>) -> io::Result<()> {
+		self.emit(&Message(Severity::Note, message.into()))
+	}
+
+	pub fn help(&mut self, message: impl Into<String>) -> io::Result<()> {
+		self.emit(&Message(Severity::Help, message.into()))
+	}
+
+	fn write(&mut self, severity: Severity, d: &dyn Display) -> io::Result<()> {
+		let rendered = d.to_string();
+		if self.dedup && !self.seen.insert(strip(&rendered)) {
+			return Ok(());
+		}
+
+		self.count[severity as usize] += 1;
+		if self.limit.is_some_and(|limit| self.shown >= limit) {
+			self.hidden += 1;
+			return Ok(());
+		}
+
+		if self.shown > 0 {
+			writeln!(self.writer)?;
+		}
+		self.shown += 1;
+		writeln!(self.writer, "{rendered}")
+	}
+
+	/// number of diagnostics emitted with `severity`, shown or not
+	pub fn count(&self, severity: Severity) -> usize {
+		self.count[severity as usize]
+	}
+
+	pub fn has_errors(&self) -> bool {
+		self.count(Severity::Error) > 0
+	}
+
+	/// `3 errors, 5 warnings emitted`, nothing if there was no error nor warning
+	pub fn summary(&mut self) -> io::Result<()> {
+		let mut part = Vec::new();
+		for (severity, name) in [(Severity::Error, "error"), (Severity::Warning, "warning")] {
+			match self.count(severity) {
+				0 => {}
+				1 => part.push(format!("1 {name}")),
+				n => part.push(format!("{n} {name}s")),
+			}
+		}
+		if part.is_empty() {
+			return Ok(());
+		}
+
+		let mut summary = format!("{} emitted", part.join(", "));
+		if self.hidden > 0 {
+			summary.push_str(&format!(" ({} not shown)", self.hidden));
+		}
+		let severity = if self.has_errors() {
+			Severity::Error
+		} else {
+			Severity::Warning
+		};
+
+		if self.shown > 0 {
+			writeln!(self.writer)?;
+		}
+		writeln!(
+			self.writer,
+			"{}",
+			display::maybe_strip(&format!("{}{summary}", theme().severity_label(severity)))
+		)
+	}
+
+	pub fn into_writer(self) -> W {
+		self.writer
+	}
+}
+
+/// a plain message, like `err!` and `warn!`
+#[derive(Debug)]
+struct Message(Severity, String);
+
+impl Diagnostic for Message {
+	fn severity(&self) -> Severity {
+		self.0
+	}
+
+	fn message(&self) -> Cow<'_, str> {
+		Cow::Borrowed(&self.1)
+	}
+}
+
+impl Display for Message {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		diagnostic::render(f, self)
+	}
+}
+
+impl Error for Message {}
+
+/// a warning shown as an error
+struct Promoted<'a>(&'a dyn Diagnostic);
+
+impl Diagnostic for Promoted<'_> {
+	fn severity(&self) -> Severity {
+		Severity::Error
+	}
+
+	fn message(&self) -> Cow<'_, str> {
+		self.0.message()
+	}
+
+	fn code(&self) -> Option<&str> {
+		self.0.code()
+	}
+
+	fn help(&self) -> Option<&str> {
+		self.0.help()
+	}
+
+	fn location(&self) -> Option<Location<'_>> {
+		self.0.location()
+	}
+
+	fn labels(&self) -> Vec<Label> {
+		self.0.labels()
+	}
+
+	fn suggestions(&self) -> &[Suggestion] {
+		self.0.suggestions()
+	}
+
+	fn source_file(&self) -> Option<&str> {
+		self.0.source_file()
+	}
+
+	fn caused_by(&self) -> Option<&(dyn Error + 'static)> {
+		self.0.caused_by()
+	}
+}
+
+impl Display for Promoted<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		diagnostic::render(f, self)
+	}
+}
+
+impl Debug for Promoted<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		Display::fmt(self, f)
+	}
+}
+
+impl Error for Promoted<'_> {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		self.0.source()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GenericError;
+
+	fn output(emitter: Emitter<Vec<u8>>) -> String {
+		strip(&String::from_utf8(emitter.into_writer()).unwrap())
+	}
+
+	#[test]
+	fn test_emitter_count() {
+		let mut emitter = Emitter::with_writer(Vec::new());
+		emitter.error("first").unwrap();
+		emitter.error("second").unwrap();
+		emitter.error("first").unwrap();
+		emitter.warning("unused").unwrap();
+		emitter.note("see the docs").unwrap();
+		emitter.help("run again").unwrap();
+		emitter
+			.emit_error(&GenericError {
+				error: String::from("third"),
+				help: None,
+				source_file: None,
+				source: None,
+			})
+			.unwrap();
+		emitter.emit_error(&std::fmt::Error).unwrap();
+		emitter.summary().unwrap();
+
+		assert_eq!(emitter.count(Severity::Error), 4);
+		assert_eq!(emitter.count(Severity::Warning), 1);
+		assert_eq!(emitter.count(Severity::Note), 1);
+		assert_eq!(emitter.count(Severity::Help), 1);
+		assert_eq!(
+			output(emitter),
+			concat!(
+				"error:\tfirst\n\n",
+				"error:\tsecond\n\n",
+				"warning:\tunused\n\n",
+				"note:\tsee the docs\n\n",
+				"help:\trun again\n\n",
+				"error:\tthird\n\n",
+				"error:\tan error occurred when formatting an argument\n\n",
+				"error:\t4 errors, 1 warning emitted\n",
+			)
+		);
+	}
+
+	/// not from hmerr, but rendered by it
+	#[derive(Debug)]
+	struct Deprecated;
+
+	impl Diagnostic for Deprecated {
+		fn severity(&self) -> Severity {
+			Severity::Warning
+		}
+
+		fn message(&self) -> Cow<'_, str> {
+			Cow::Borrowed("`vt` is deprecated")
+		}
+	}
+
+	impl Display for Deprecated {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			diagnostic::render(f, self)
+		}
+	}
+
+	impl Error for Deprecated {}
+
+	#[derive(Debug)]
+	struct Wrapper(Deprecated);
+
+	impl Display for Wrapper {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			write!(f, "config: {}", self.0)
+		}
+	}
+
+	impl Error for Wrapper {}
+
+	#[test]
+	fn test_emitter_rendered_error() {
+		let mut emitter = Emitter::with_writer(Vec::new());
+		emitter.emit_error(&Deprecated).unwrap();
+		assert_eq!(emitter.count(Severity::Warning), 1);
+		assert_eq!(output(emitter), "warning:\t`vt` is deprecated\n");
+
+		// its own text around the rendered error is kept
+		let mut emitter = Emitter::with_writer(Vec::new());
+		emitter.emit_error(&Wrapper(Deprecated)).unwrap();
+		assert_eq!(emitter.count(Severity::Error), 1);
+		let s = output(emitter);
+		assert!(s.starts_with("error:\tconfig: "));
+		assert!(s.contains("`vt` is deprecated"));
+	}
+
+	#[test]
+	fn test_emitter_options() {
+		let mut emitter = Emitter::with_writer(Vec::new())
+			.warnings_as_errors(true)
+			.dedup(false)
+			.limit(2);
+		emitter.warning("unused").unwrap();
+		emitter.warning("unused").unwrap();
+		emitter.error("hidden").unwrap();
+		emitter.summary().unwrap();
+
+		assert!(emitter.has_errors());
+		assert_eq!(emitter.count(Severity::Warning), 0);
+		assert_eq!(
+			output(emitter),
+			concat!(
+				"error:\tunused\n\n",
+				"error:\tunused\n\n",
+				"error:\t3 errors emitted (1 not shown)\n",
+			)
+		);
+
+		// nothing to sum up
+		let mut emitter = Emitter::with_writer(Vec::new());
+		emitter.note("fine").unwrap();
+		emitter.summary().unwrap();
+		assert_eq!(output(emitter), "note:\tfine\n");
+	}
+}
@@ -0,0 +1,185 @@
+//! long-form explanations of error codes, like rustc's error index
+//!
+//! crates register the explanations of their codes once,
+//! a `--explain CODE` option then only has to print `explain::get(code)`
+
+use crate::display::{self, RESET, theme};
+
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display};
+use std::sync::RwLock;
+
+/// what an error code means, with examples of wrong and right input
+///
+/// ```
+/// use hmerr::display::Plain;
+/// use hmerr::explain::{self, Explanation};
+///
+/// explain::register(
+///     Explanation::new("OBJ0001", "unsupported keyword", "every line starts with a keyword")
+///         .wrong("tv 1 2")
+///         .right("vt 1 2"),
+/// );
+///
+/// let e = explain::get("OBJ0001").unwrap();
+/// assert!(Plain(&e).to_string().starts_with("OBJ0001: unsupported keyword\n"));
+/// assert!(explain::get("OBJ4242").is_none());
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Explanation {
+	pub code: String,
+	pub title: String,
+	pub description: String,
+	pub wrong: Vec<String>, // examples of input causing the error
+	pub right: Vec<String>, // the same examples, fixed
+	pub link: Vec<String>,
+}
+
+impl Explanation {
+	pub fn new(
+		code: impl Into<String>,
+		title: impl Into<String>,
+		description: impl Into<String>,
+	) -> Self {
+		Self {
+			code: code.into(),
+			title: title.into(),
+			description: description.into(),
+			..Default::default()
+		}
+	}
+
+	pub fn wrong(mut self, example: impl Into<String>) -> Self {
+		self.wrong.push(example.into());
+		self
+	}
+
+	pub fn right(mut self, example: impl Into<String>) -> Self {
+		self.right.push(example.into());
+		self
+	}
+
+	pub fn link(mut self, link: impl Into<String>) -> Self {
+		self.link.push(link.into());
+		self
+	}
+}
+
+static REGISTRY: RwLock<BTreeMap<String, Explanation>> = RwLock::new(BTreeMap::new());
+
+/// add `explanation`, replacing the one already registered for its code
+pub fn register(explanation: Explanation) {
+	REGISTRY
+		.write()
+		.unwrap_or_else(|e| e.into_inner())
+		.insert(explanation.code.clone(), explanation);
+}
+
+/// explanation of `code`, if registered
+pub fn get(code: &str) -> Option<Explanation> {
+	REGISTRY
+		.read()
+		.unwrap_or_else(|e| e.into_inner())
+		.get(code)
+		.cloned()
+}
+
+/// every registered code, sorted
+pub fn codes() -> Vec<String> {
+	REGISTRY
+		.read()
+		.unwrap_or_else(|e| e.into_inner())
+		.keys()
+		.cloned()
+		.collect()
+}
+
+impl Display for Explanation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		display::render(f, |f| {
+			let t = theme();
+
+			write!(
+				f,
+				"{RESET}{c}{code}{RESET}{b}: {title}{RESET}",
+				c = t.error,
+				b = t.bold,
+				code = self.code,
+				title = self.title
+			)?;
+			if !self.description.is_empty() {
+				write!(f, "\n\n{}", self.description)?;
+			}
+
+			for (label, color, example) in [
+				("wrong", &t.got, &self.wrong),
+				("right", &t.expected, &self.right),
+			] {
+				if example.is_empty() {
+					continue;
+				}
+				write!(f, "\n\n{RESET}{l}{label}:{RESET}", l = t.label)?;
+				for e in example {
+					write!(f, "\n{color}\t{e}{RESET}", e = e.replace('\n', "\n\t"))?;
+				}
+			}
+
+			if !self.link.is_empty() {
+				write!(f, "\n\n{RESET}{l}see:{RESET}", l = t.label)?;
+				for link in &self.link {
+					write!(f, "\n\t{RESET}{c}{link}{RESET}", c = t.file)?;
+				}
+			}
+
+			Ok(())
+		})
+	}
+}
+
+impl Debug for Explanation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{clear}{self}", clear = display::clear_line())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::display::Plain;
+
+	#[test]
+	fn test_explain_display() {
+		let e = Explanation::new(
+			"TEST0001",
+			"missing value",
+			"a key must be followed by its value",
+		)
+		.wrong("name")
+		.right("name John")
+		.right("name\tJohn")
+		.link("https://example.com/TEST0001");
+
+		assert_eq!(
+			Plain(&e).to_string(),
+			concat!(
+				"TEST0001: missing value\n",
+				"\n",
+				"a key must be followed by its value\n",
+				"\n",
+				"wrong:\n",
+				"\tname\n",
+				"\n",
+				"right:\n",
+				"\tname John\n",
+				"\tname\tJohn\n",
+				"\n",
+				"see:\n",
+				"\thttps://example.com/TEST0001",
+			)
+		);
+
+		register(e.clone());
+		assert_eq!(get("TEST0001"), Some(e));
+		assert!(codes().contains(&"TEST0001".to_string()));
+	}
+}
@@ -0,0 +1,120 @@
+use std::fmt::{Display, Formatter, Write};
+
+/// minimal JSON value, enough to export errors without any dependency
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+	Null,
+	Bool(bool),
+	Number(i64),
+	String(String),
+	Array(Vec<Value>),
+	Object(Vec<(&'static str, Value)>),
+}
+
+impl From<&str> for Value {
+	fn from(s: &str) -> Self {
+		Self::String(s.to_string())
+	}
+}
+
+impl From<String> for Value {
+	fn from(s: String) -> Self {
+		Self::String(s)
+	}
+}
+
+impl From<bool> for Value {
+	fn from(b: bool) -> Self {
+		Self::Bool(b)
+	}
+}
+
+impl From<usize> for Value {
+	fn from(n: usize) -> Self {
+		Self::Number(n as i64)
+	}
+}
+
+impl From<i32> for Value {
+	fn from(n: i32) -> Self {
+		Self::Number(n.into())
+	}
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+	fn from(v: Option<T>) -> Self {
+		v.map_or(Self::Null, Into::into)
+	}
+}
+
+impl Display for Value {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Null => f.write_str("null"),
+			Self::Bool(b) => write!(f, "{b}"),
+			Self::Number(n) => write!(f, "{n}"),
+			Self::String(s) => escape(f, s),
+			Self::Array(v) => {
+				f.write_char('[')?;
+				for (i, x) in v.iter().enumerate() {
+					if i > 0 {
+						f.write_char(',')?;
+					}
+					write!(f, "{x}")?;
+				}
+				f.write_char(']')
+			}
+			Self::Object(v) => {
+				f.write_char('{')?;
+				for (i, (k, x)) in v.iter().enumerate() {
+					if i > 0 {
+						f.write_char(',')?;
+					}
+					escape(f, k)?;
+					write!(f, ":{x}")?;
+				}
+				f.write_char('}')
+			}
+		}
+	}
+}
+
+fn escape(f: &mut Formatter<'_>, s: &str) -> std::fmt::Result {
+	f.write_char('"')?;
+	for c in s.chars() {
+		match c {
+			'"' => f.write_str("\\\"")?,
+			'\\' => f.write_str("\\\\")?,
+			'\n' => f.write_str("\\n")?,
+			'\r' => f.write_str("\\r")?,
+			'\t' => f.write_str("\\t")?,
+			c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+			c => f.write_char(c)?,
+		}
+	}
+	f.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_value() {
+		let v = Value::Object(vec![
+			("a", Value::Null),
+			("b", 42usize.into()),
+			("c", "q\"u\\o\nte\x1b".into()),
+			(
+				"d",
+				Value::Array(vec![1.into(), Some("x").into(), None::<&str>.into()]),
+			),
+			("e", Value::Object(Vec::new())),
+			("f", true.into()),
+		]);
+		assert_eq!(
+			v.to_string(),
+			r#"{"a":null,"b":42,"c":"q\"u\\o\nte\u001b","d":[1,"x",null],"e":{},"f":true}"#
+		);
+	}
+}
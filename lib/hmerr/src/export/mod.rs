@@ -0,0 +1,283 @@
+//! machine readable output of errors
+//!
+//! every string is stripped of its ANSI escapes,
+//! line indexes and byte ranges are the ones given to the error (usually 0 based)
+
+mod json;
+pub(crate) use json::Value;
+mod sarif;
+pub use sarif::Sarif;
+
+use crate::diagnostic::{Diagnostic, as_diagnostic};
+use crate::display::strip;
+use crate::{GenericError, IoError, ParseFileError, StandardError};
+
+use std::error::Error;
+
+/// `e` as a JSON object, with its chain of causes
+///
+/// ```
+/// use hmerr::{export, ge};
+///
+/// let e = ge!("could not load config", h:"check the path");
+/// let json = export::to_json(&e);
+/// assert!(json.starts_with(concat!(
+///     r#"{"kind":"generic","severity":"error","code":null,"message":"could not load config","#,
+///     r#""help":"check the path","file":null,"line":null,"snippet":null,"#,
+/// )));
+/// assert!(json.ends_with(r#""cause":[]}"#));
+/// ```
+pub fn to_json(e: &(dyn Error + 'static)) -> String {
+	with_cause(describe(e), cause(e)).to_string()
+}
+
+/// `d` as a JSON object, with its chain of causes
+///
+/// for a user type implementing `Diagnostic`, its `kind` is `diagnostic`
+pub fn diagnostic_to_json(d: &dyn Diagnostic) -> String {
+	let cause = d
+		.caused_by()
+		.into_iter()
+		.flat_map(|e| std::iter::once(e).chain(cause(e)));
+
+	with_cause(describe_diagnostic(d, "diagnostic", Vec::new()), cause).to_string()
+}
+
+fn with_cause<'a>(v: Value, cause: impl Iterator<Item = &'a (dyn Error + 'static)>) -> Value {
+	let Value::Object(mut field) = v else {
+		unreachable!("an error is always described by an object");
+	};
+
+	field.push(("cause", Value::Array(cause.map(describe).collect())));
+
+	Value::Object(field)
+}
+
+/// every error that caused `e`, the direct cause first
+pub(crate) fn cause<'a>(
+	e: &'a (dyn Error + 'static),
+) -> impl Iterator<Item = &'a (dyn Error + 'static)> {
+	std::iter::successors(e.source(), |e| (*e).source())
+}
+
+/// `e` as a JSON object, without its causes
+pub(crate) fn describe(e: &(dyn Error + 'static)) -> Value {
+	let Some(d) = as_diagnostic(e) else {
+		return Value::Object(vec![
+			("kind", "other".into()),
+			("message", strip(&e.to_string()).into()),
+		]);
+	};
+
+	if let Some(e) = e.downcast_ref::<StandardError>() {
+		describe_diagnostic(
+			d,
+			"standard",
+			vec![
+				("expected", strip(&e.expected).into()),
+				("got", strip(&e.got).into()),
+			],
+		)
+	} else if let Some(e) = e.downcast_ref::<IoError>() {
+		describe_diagnostic(
+			d,
+			"io",
+			vec![
+				("io_kind", format!("{:?}", e.error.kind()).into()),
+				("os_error", e.error.raw_os_error().into()),
+			],
+		)
+	} else if e.is::<ParseFileError>() {
+		describe_diagnostic(d, "parse", Vec::new())
+	} else {
+		describe_diagnostic(d, "generic", Vec::new())
+	}
+}
+
+/// fields shared by every diagnostic, `extra` goes right after the message
+pub(crate) fn describe_diagnostic(
+	d: &dyn Diagnostic,
+	kind: &'static str,
+	extra: Vec<(&'static str, Value)>,
+) -> Value {
+	let location = d.location();
+	let line = location.and_then(|l| l.line).map_or(Value::Null, |l| {
+		Value::Object(vec![
+			("index", l.index.into()),
+			("content", l.line.as_str().into()),
+			(
+				"labels",
+				Value::Array(
+					d.labels()
+						.into_iter()
+						.map(|l| {
+							Value::Object(vec![
+								("start", l.range.start.into()),
+								("end", l.range.end.into()),
+								("message", l.message.as_deref().map(strip).into()),
+								("primary", l.primary.into()),
+							])
+						})
+						.collect(),
+				),
+			),
+		])
+	});
+
+	let snippet = location.and_then(|l| l.snippet).map_or(Value::Null, |s| {
+		let position = |(line, column): (usize, usize)| {
+			Value::Object(vec![("line", line.into()), ("column", column.into())])
+		};
+
+		Value::Object(vec![
+			("first", s.first.into()),
+			(
+				"lines",
+				Value::Array(s.line.iter().map(|l| l.as_str().into()).collect()),
+			),
+			(
+				"highlights",
+				Value::Array(
+					s.highlight
+						.iter()
+						.map(|h| {
+							Value::Object(vec![
+								("start", position(h.start)),
+								("end", position(h.end)),
+								("message", h.message.as_deref().map(strip).into()),
+								("primary", h.primary.into()),
+							])
+						})
+						.collect(),
+				),
+			),
+		])
+	});
+
+	let suggestion = d
+		.suggestions()
+		.iter()
+		.map(|s| {
+			Value::Object(vec![
+				("message", s.message.as_deref().map(strip).into()),
+				(
+					"edits",
+					Value::Array(
+						s.edit
+							.iter()
+							.map(|e| {
+								Value::Object(vec![
+									("line", e.line.into()),
+									("start", e.range.start.into()),
+									("end", e.range.end.into()),
+									("text", e.text.as_str().into()),
+								])
+							})
+							.collect(),
+					),
+				),
+			])
+		})
+		.collect();
+
+	let mut field = vec![
+		("kind", kind.into()),
+		("severity", d.severity().as_str().into()),
+		("code", d.code().into()),
+		("message", strip(&d.message()).into()),
+	];
+	field.extend(extra);
+	field.extend([
+		("help", d.help().map(strip).into()),
+		("file", location.and_then(|l| l.file).into()),
+		("line", line),
+		("snippet", snippet),
+		("suggestions", Value::Array(suggestion)),
+		("source_file", d.source_file().into()),
+	]);
+
+	Value::Object(field)
+}
+
+impl ParseFileError {
+	/// see `export::to_json`
+	pub fn to_json(&self) -> String {
+		to_json(self)
+	}
+}
+
+impl StandardError {
+	/// see `export::to_json`
+	pub fn to_json(&self) -> String {
+		to_json(self)
+	}
+}
+
+impl IoError {
+	/// see `export::to_json`
+	pub fn to_json(&self) -> String {
+		to_json(self)
+	}
+}
+
+impl GenericError {
+	/// see `export::to_json`
+	pub fn to_json(&self) -> String {
+		to_json(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::parse::Line;
+
+	#[test]
+	fn test_to_json_parse() {
+		let cause = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+		let e = ParseFileError::new(
+			"\x1b[1minvalid\x1b[0m value",
+			"f.conf".to_string(),
+			Some(Line::new(
+				"key val val",
+				Some(3),
+				vec![(4, 3).into(), "val".into()],
+			)),
+			"help".to_string(),
+			"src/x.rs".to_string(),
+			Some(Box::new(IoError {
+				file: String::from("f.conf"),
+				error: cause,
+				help: None,
+				source_file: None,
+				source: None,
+			})),
+		)
+		.with_code("TEST0002");
+
+		assert_eq!(
+			e.to_json(),
+			concat!(
+				r#"{"kind":"parse","severity":"error","code":"TEST0002","message":"invalid value","#,
+				r#""help":"help","#,
+				r#""file":"f.conf","line":{"index":3,"content":"key val val","labels":["#,
+				r#"{"start":4,"end":7,"message":null,"primary":true},"#,
+				r#"{"start":8,"end":11,"message":null,"primary":true}]},"snippet":null,"#,
+				r#""suggestions":[],"source_file":"src/x.rs","cause":[{"kind":"io","severity":"error","code":null,"#,
+				r#""message":"f.conf not found: gone","io_kind":"NotFound","os_error":null,"#,
+				r#""help":"check the path, a relative one starts from the current directory","#,
+				r#""file":"f.conf","line":null,"snippet":null,"suggestions":[],"#,
+				r#""source_file":null}]}"#,
+			)
+		);
+	}
+
+	#[test]
+	fn test_to_json_other() {
+		let e = std::fmt::Error;
+		assert_eq!(
+			to_json(&e),
+			r#"{"kind":"other","message":"an error occurred when formatting an argument","cause":[]}"#
+		);
+	}
+}
@@ -0,0 +1,399 @@
+use super::{Value, cause, describe, describe_diagnostic};
+
+use crate::diagnostic::Diagnostic;
+use crate::explain;
+
+use std::error::Error;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// SARIF 2.1.0 log, to upload errors to code scanning dashboards
+///
+/// a `ParseFileError` with a file is reported at its line (`index + 1`, SARIF lines are 1 based)
+/// and its first primary label, every cause is added to the message of the result
+///
+/// the code of an error is its `ruleId`, described in the driver `rules` if it is registered in `explain`
+///
+/// ```
+/// use hmerr::export::Sarif;
+/// use hmerr::{ParseFileError, ple, pwe};
+///
+/// let e = ParseFileError::new(
+///     "unsupported keyword",
+///     "scene.obj".to_string(),
+///     Some(ple!("vt 1 2", i: 3, w: pwe!("vt"))),
+///     None::<String>,
+///     None::<String>,
+///     None,
+/// );
+///
+/// let mut log = Sarif::new("spof");
+/// log.push(&e);
+/// assert!(log.to_json().contains(r#""region":{"startLine":4,"startColumn":1,"endColumn":3"#));
+/// ```
+pub struct Sarif {
+	pub tool: String,
+	pub version: Option<String>,
+	result: Vec<Value>,
+}
+
+impl Sarif {
+	pub fn new(tool: impl Into<String>) -> Self {
+		Self {
+			tool: tool.into(),
+			version: None,
+			result: Vec::new(),
+		}
+	}
+
+	pub fn version(mut self, version: impl Into<String>) -> Self {
+		self.version = Some(version.into());
+		self
+	}
+
+	pub fn push(&mut self, e: &(dyn Error + 'static)) {
+		self.result.push(result(describe(e), cause(e)));
+	}
+
+	/// add a user type implementing `Diagnostic`
+	pub fn push_diagnostic(&mut self, d: &dyn Diagnostic) {
+		let cause = d
+			.caused_by()
+			.into_iter()
+			.flat_map(|e| std::iter::once(e).chain(cause(e)));
+
+		self.result.push(result(
+			describe_diagnostic(d, "diagnostic", Vec::new()),
+			cause,
+		));
+	}
+
+	pub fn len(&self) -> usize {
+		self.result.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.result.is_empty()
+	}
+
+	pub fn to_json(&self) -> String {
+		let mut driver = vec![("name", self.tool.as_str().into())];
+		if let Some(version) = &self.version {
+			driver.push(("version", version.as_str().into()));
+		}
+		let rule = self.rules();
+		if !rule.is_empty() {
+			driver.push(("rules", Value::Array(rule)));
+		}
+
+		Value::Object(vec![
+			("version", "2.1.0".into()),
+			("$schema", SCHEMA.into()),
+			(
+				"runs",
+				Value::Array(vec![Value::Object(vec![
+					(
+						"tool",
+						Value::Object(vec![("driver", Value::Object(driver))]),
+					),
+					("results", Value::Array(self.result.clone())),
+				])]),
+			),
+		])
+		.to_string()
+	}
+}
+
+impl Sarif {
+	/// registered explanation of every code of the results, once each
+	fn rules(&self) -> Vec<Value> {
+		let mut code: Vec<&str> = self
+			.result
+			.iter()
+			.filter_map(|r| string(r, "ruleId"))
+			.collect();
+		code.sort_unstable();
+		code.dedup();
+
+		code.into_iter()
+			.filter_map(explain::get)
+			.map(|e| {
+				let mut rule = vec![
+					("id", e.code.as_str().into()),
+					(
+						"shortDescription",
+						Value::Object(vec![("text", e.title.as_str().into())]),
+					),
+				];
+				if !e.description.is_empty() {
+					rule.push((
+						"fullDescription",
+						Value::Object(vec![("text", e.description.as_str().into())]),
+					));
+				}
+				if let Some(link) = e.link.first() {
+					rule.push(("helpUri", link.as_str().into()));
+				}
+				Value::Object(rule)
+			})
+			.collect()
+	}
+}
+
+fn result<'a>(d: Value, cause: impl Iterator<Item = &'a (dyn Error + 'static)>) -> Value {
+	let mut text = string(&d, "message").unwrap_or_default().to_string();
+	if let Some(help) = string(&d, "help") {
+		text.push_str("\nhelp: ");
+		text.push_str(help);
+	}
+	for cause in cause {
+		text.push_str("\ncaused by: ");
+		text.push_str(string(&describe(cause), "message").unwrap_or_default());
+	}
+
+	let mut field = Vec::new();
+	if let Some(code) = string(&d, "code") {
+		field.push(("ruleId", code.into()));
+	}
+	field.extend([
+		("level", level(string(&d, "severity")).into()),
+		("message", Value::Object(vec![("text", text.into())])),
+	]);
+	if let Some(location) = location(&d) {
+		field.push(("locations", Value::Array(vec![location])));
+	}
+
+	Value::Object(field)
+}
+
+fn level(severity: Option<&str>) -> &'static str {
+	match severity {
+		Some("warning") => "warning",
+		Some("note" | "help") => "note",
+		_ => "error",
+	}
+}
+
+fn location(d: &Value) -> Option<Value> {
+	let file = string(d, "file")?;
+
+	let mut physical = vec![(
+		"artifactLocation",
+		Value::Object(vec![("uri", file.into())]),
+	)];
+	if let Some(line) = get(d, "line")
+		&& let Some(Value::Number(index)) = get(line, "index")
+	{
+		let content = string(line, "content").unwrap_or_default();
+		let mut region = vec![("startLine", Value::Number(index + 1))];
+		if let Some(Value::Array(range)) = get(line, "labels")
+			&& let Some(r) = range
+				.iter()
+				.find(|r| get(r, "primary") == Some(&Value::Bool(true)))
+				.or(range.first())
+			&& let (Some(Value::Number(start)), Some(Value::Number(end))) =
+				(get(r, "start"), get(r, "end"))
+		{
+			region.push(("startColumn", column(content, *start as usize).into()));
+			region.push(("endColumn", column(content, *end as usize).into()));
+		}
+		region.push(("snippet", Value::Object(vec![("text", content.into())])));
+		physical.push(("region", Value::Object(region)));
+	} else if let Some(snippet) = get(d, "snippet")
+		&& let Some(region) = snippet_region(snippet)
+	{
+		physical.push(("region", region));
+	}
+
+	Some(Value::Object(vec![(
+		"physicalLocation",
+		Value::Object(physical),
+	)]))
+}
+
+/// region of the primary highlight of a snippet, which can span several lines
+fn snippet_region(snippet: &Value) -> Option<Value> {
+	let (Some(Value::Number(first)), Some(Value::Array(line)), Some(Value::Array(highlight))) = (
+		get(snippet, "first"),
+		get(snippet, "lines"),
+		get(snippet, "highlights"),
+	) else {
+		return None;
+	};
+	let h = highlight
+		.iter()
+		.find(|h| get(h, "primary") == Some(&Value::Bool(true)))
+		.or(highlight.first())?;
+
+	let position = |key: &str| {
+		let p = get(h, key)?;
+		let (Some(Value::Number(index)), Some(Value::Number(byte))) =
+			(get(p, "line"), get(p, "column"))
+		else {
+			return None;
+		};
+		let content = match line.get(index.checked_sub(*first)? as usize) {
+			Some(Value::String(s)) => s.as_str(),
+			_ => "",
+		};
+		Some((index + 1, column(content, *byte as usize)))
+	};
+	let (start_line, start_column) = position("start")?;
+	let (end_line, end_column) = position("end")?;
+
+	Some(Value::Object(vec![
+		("startLine", Value::Number(start_line)),
+		("startColumn", start_column.into()),
+		("endLine", Value::Number(end_line)),
+		("endColumn", end_column.into()),
+	]))
+}
+
+/// 1 based column in UTF-16 code units (SARIF default), from a byte index
+fn column(line: &str, byte: usize) -> usize {
+	let mut byte = byte.min(line.len());
+	while !line.is_char_boundary(byte) {
+		byte -= 1;
+	}
+	line[..byte].encode_utf16().count() + 1
+}
+
+fn get<'a>(v: &'a Value, key: &str) -> Option<&'a Value> {
+	match v {
+		Value::Object(field) => field.iter().find(|(k, _)| *k == key).map(|(_, v)| v),
+		_ => None,
+	}
+}
+
+fn string<'a>(v: &'a Value, key: &str) -> Option<&'a str> {
+	match get(v, key)? {
+		Value::String(s) => Some(s),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GenericError;
+
+	#[test]
+	fn test_sarif() {
+		let mut log = Sarif::new("spof").version("0.1.0");
+		assert!(log.is_empty());
+
+		log.push(&GenericError {
+			error: String::from("oops"),
+			help: Some(String::from("retry")),
+			source_file: None,
+			source: Some(Box::new(std::fmt::Error)),
+		});
+		assert_eq!(log.len(), 1);
+
+		assert_eq!(
+			log.to_json(),
+			concat!(
+				r#"{"version":"2.1.0","$schema":"https://json.schemastore.org/sarif-2.1.0.json","#,
+				r#""runs":[{"tool":{"driver":{"name":"spof","version":"0.1.0"}},"#,
+				r#""results":[{"level":"error","message":{"text":"oops\nhelp: retry\ncaused by: "#,
+				r#"an error occurred when formatting an argument"}}]}]}"#,
+			)
+		);
+	}
+
+	#[test]
+	fn test_sarif_rule() {
+		use crate::ParseFileError;
+		use crate::explain::{self, Explanation};
+
+		explain::register(
+			Explanation::new("TEST0003", "unsupported keyword", "")
+				.link("https://example.com/TEST0003"),
+		);
+
+		let mut log = Sarif::new("spof");
+		for code in ["TEST0003", "TEST0003", "TEST4242"] {
+			log.push(
+				&ParseFileError::new("unsupported keyword", None, None, None, None, None)
+					.with_code(code),
+			);
+		}
+
+		let json = log.to_json();
+		assert!(json.contains(concat!(
+			r#""rules":[{"id":"TEST0003","shortDescription":{"text":"unsupported keyword"},"#,
+			r#""helpUri":"https://example.com/TEST0003"}]"#,
+		)));
+		assert!(json.contains(r#"{"ruleId":"TEST4242","level":"error","#));
+	}
+
+	#[test]
+	fn test_sarif_level() {
+		use crate::diagnostic::Severity;
+		use std::borrow::Cow;
+
+		#[derive(Debug)]
+		struct Unused;
+
+		impl std::fmt::Display for Unused {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				crate::diagnostic::render(f, self)
+			}
+		}
+
+		impl Error for Unused {}
+
+		impl Diagnostic for Unused {
+			fn severity(&self) -> Severity {
+				Severity::Warning
+			}
+
+			fn message(&self) -> Cow<'_, str> {
+				Cow::Borrowed("unused keyword")
+			}
+		}
+
+		let mut log = Sarif::new("spof");
+		log.push_diagnostic(&Unused);
+		assert!(
+			log.to_json()
+				.contains(r#""results":[{"level":"warning","message":{"text":"unused keyword"}}]"#)
+		);
+	}
+
+	#[test]
+	fn test_sarif_snippet() {
+		use crate::ParseFileError;
+		use crate::parse::{Highlight, Snippet};
+
+		let e = ParseFileError::new(
+			"unterminated block",
+			"f.conf".to_string(),
+			None,
+			None::<String>,
+			None::<String>,
+			None,
+		)
+		.with_snippet(Snippet::new(
+			"a 1\nblock {\n\tb 2",
+			vec![Highlight::primary((1, 6), (2, 4), "opened here")],
+			1,
+		));
+
+		let mut log = Sarif::new("spof");
+		log.push(&e);
+		assert!(
+			log.to_json()
+				.contains(r#""region":{"startLine":2,"startColumn":7,"endLine":3,"endColumn":5}"#)
+		);
+	}
+
+	#[test]
+	fn test_column() {
+		assert_eq!(column("abc", 0), 1);
+		assert_eq!(column("é𝄞x", 2), 2);
+		assert_eq!(column("é𝄞x", 6), 4);
+		assert_eq!(column("é𝄞x", 7), 5);
+		assert_eq!(column("é𝄞x", 42), 5);
+	}
+}
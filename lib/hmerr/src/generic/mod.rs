@@ -25,7 +25,7 @@ impl Error for GenericError {
 ///
 /// optional don't need to be defined (don't even need `None`)
 ///
-/// but they must be in order (for now)
+/// they can be given in any order, but only once
 ///
 /// # Example
 ///
@@ -63,12 +63,33 @@ impl Error for GenericError {
 macro_rules! ge {
 	(@some_or_none) => { None };
 	(@some_or_none $entity:expr) => { Some($entity) };
-    ($error:expr $(, h:$help:expr)? $(, s:$source:expr)?$(,)?) => {
+	(@parse ($error:expr) [($($help:expr)?) ($($source:expr)?)]) => {
 		$crate::generic::GenericError {
 			error: $error.into(),
-			help: ge!(@some_or_none $($help.into())?),
+			help: $crate::ge!(@some_or_none $($help.into())?),
 			source_file: Some(file!().to_string()),
-			source: ge!(@some_or_none $(Box::new($source))?),
+			source: $crate::ge!(@some_or_none $(Box::new($source))?),
 		}
-    };
+	};
+	(@parse $p:tt [() $s:tt] h:$help:expr $(, $($rest:tt)*)?) => {
+		$crate::ge!(@parse $p [($help) $s] $($($rest)*)?)
+	};
+	(@parse $p:tt [$h:tt ()] s:$source:expr $(, $($rest:tt)*)?) => {
+		$crate::ge!(@parse $p [$h ($source)] $($($rest)*)?)
+	};
+	(@parse $p:tt [($($h:tt)+) $s:tt] h: $($rest:tt)*) => {
+		compile_error!("`h:` (help) is given more than once to `ge!`")
+	};
+	(@parse $p:tt [$h:tt ($($s:tt)+)] s: $($rest:tt)*) => {
+		compile_error!("`s:` (source) is given more than once to `ge!`")
+	};
+	(@parse $p:tt $slot:tt $($rest:tt)*) => {
+		compile_error!(concat!(
+			"unexpected `", stringify!($($rest)*), "` in `ge!`, ",
+			"the optional arguments are `h:help` and `s:source`"
+		))
+	};
+	($error:expr $(, $($arg:tt)*)?) => {
+		$crate::ge!(@parse ($error) [() ()] $($($arg)*)?)
+	};
 }
@@ -1,35 +1,194 @@
 use super::IoError;
 
-use crate::display::write;
+use crate::diagnostic::Diagnostic;
+use crate::display::{self, RESET, theme, write};
 
+use std::borrow::Cow;
 use std::fmt::{Debug, Display};
-use std::io;
+use std::io::{self, ErrorKind};
 
-fn display_error(error: &io::Error, file: &str) -> String {
-	// TODO: handle more error
-	match error.kind() {
-		io::ErrorKind::NotFound => format!("\x1b[1;35m{file} \x1b[1;31mnot found\x1b[0m"),
-		_ => format!("{kind} {error}", kind = error.kind()),
+/// what happened to the file, and how to fix it
+fn describe(kind: ErrorKind) -> (Cow<'static, str>, Option<&'static str>) {
+	let (what, help) = match kind {
+		ErrorKind::NotFound => (
+			"not found",
+			Some("check the path, a relative one starts from the current directory"),
+		),
+		ErrorKind::PermissionDenied => (
+			"permission denied",
+			Some("check the permissions of the file and of its parent directory (`ls -ld`)"),
+		),
+		ErrorKind::AlreadyExists => ("already exists", Some("remove it, or choose another path")),
+		ErrorKind::IsADirectory => (
+			"is a directory",
+			Some("give the path of a file, not of a directory"),
+		),
+		ErrorKind::NotADirectory => (
+			"is not a directory",
+			Some("a part of the path is a file, not a directory"),
+		),
+		ErrorKind::DirectoryNotEmpty => (
+			"is not an empty directory",
+			Some("remove its content first"),
+		),
+		ErrorKind::ReadOnlyFilesystem => (
+			"is on a read-only filesystem",
+			Some("write somewhere else, or remount the filesystem as writable"),
+		),
+		ErrorKind::StorageFull => (
+			"could not be written, no space left on the device",
+			Some("free some space on the device"),
+		),
+		ErrorKind::QuotaExceeded => (
+			"could not be written, disk quota exceeded",
+			Some("free some space, or ask for a larger quota"),
+		),
+		ErrorKind::FileTooLarge => ("is too large", None),
+		ErrorKind::ResourceBusy => ("is busy", Some("close the programs using it and retry")),
+		ErrorKind::ExecutableFileBusy => (
+			"is a running executable",
+			Some("stop it before writing to it"),
+		),
+		ErrorKind::InvalidFilename => (
+			"is not a valid file name",
+			Some("shorten it, or remove the characters not allowed on this platform"),
+		),
+		ErrorKind::InvalidData => (
+			"contains invalid data",
+			Some("make sure the file has the expected format and is encoded in UTF-8"),
+		),
+		ErrorKind::UnexpectedEof => (
+			"ended unexpectedly",
+			Some("the file may be truncated, or still being written"),
+		),
+		ErrorKind::BrokenPipe => (
+			"was closed while being written (broken pipe)",
+			Some("the program reading it exited before the end"),
+		),
+		ErrorKind::TimedOut => (
+			"timed out",
+			Some("retry, and check the network or the device the file is on"),
+		),
+		ErrorKind::Interrupted => ("was interrupted", Some("retry")),
+		ErrorKind::WouldBlock => ("would block", None),
+		ErrorKind::CrossesDevices => (
+			"cannot be moved to another filesystem",
+			Some("copy it, then remove the original"),
+		),
+		ErrorKind::StaleNetworkFileHandle => (
+			"is a stale network file handle",
+			Some("remount the network filesystem"),
+		),
+		ErrorKind::Unsupported => ("does not support this operation", None),
+		kind => return (Cow::Owned(kind.to_string()), None),
+	};
+
+	(Cow::Borrowed(what), help)
+}
+
+/// help deduced from the kind of the error, used when `IoError::help` is not set
+pub(crate) fn help(error: &io::Error) -> Option<&'static str> {
+	describe(error.kind()).1
+}
+
+pub(crate) fn display_error(error: &io::Error, file: &str) -> String {
+	let t = theme();
+	let mut message = format!(
+		"{RESET}{f}{file} {RESET}{e}{what}{RESET}",
+		f = t.file,
+		e = t.error,
+		what = describe(error.kind()).0
+	);
+
+	// message given with `io::Error::new`
+	if let Some(inner) = error.get_ref() {
+		message.push_str(&format!(": {inner}"));
+	}
+	if let Some(code) = error.raw_os_error() {
+		message.push_str(&format!(" {c}(os error {code}){RESET}", c = t.label));
 	}
+
+	message
 }
 
 impl Display for IoError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		let padding = write::padding(self.source.as_ref(), None);
+		display::render(f, |f| {
+			let padding = write::padding(self.source.as_ref(), None);
 
-		write::error(f, &display_error(&self.error, &self.file))?;
-		if let Some(help) = &self.help {
-			write::help(f, &padding, Some(help))?;
-		}
-		write::source_file(f, &padding, self.source_file.as_deref())?;
-		write::source(f, self.source.as_ref())?;
+			write::error(f, &display_error(&self.error, &self.file))?;
+			if let Some(help) = Diagnostic::help(self) {
+				write::help(f, &padding, Some(help))?;
+			}
+			write::source_file(f, &padding, self.source_file.as_deref())?;
+			write::source(f, self.source.as_ref())?;
 
-		Ok(())
+			Ok(())
+		})
 	}
 }
 
 impl Debug for IoError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "\x1b[2K\r{self}")
+		write!(f, "{clear}{self}", clear = display::clear_line())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::display::Plain;
+
+	fn io_error(error: io::Error, help: Option<&str>) -> IoError {
+		IoError {
+			file: String::from("f.conf"),
+			error,
+			help: help.map(String::from),
+			source_file: None,
+			source: None,
+		}
+	}
+
+	#[test]
+	fn test_display_kind() {
+		assert_eq!(
+			Plain(io_error(ErrorKind::IsADirectory.into(), None)).to_string(),
+			"error:\tf.conf is a directory\n ╧ help: give the path of a file, not of a directory"
+		);
+		assert_eq!(
+			Plain(io_error(
+				io::Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8"),
+				Some("save it as UTF-8"),
+			))
+			.to_string(),
+			"error:\tf.conf contains invalid data: stream did not contain valid UTF-8\n ╧ help: save it as UTF-8"
+		);
+		// no help to deduce
+		assert_eq!(
+			Plain(io_error(ErrorKind::Other.into(), None)).to_string(),
+			"error:\tf.conf other error"
+		);
+	}
+
+	#[test]
+	fn test_display_theme() {
+		let t = theme();
+		let s = display_error(&ErrorKind::NotFound.into(), "f.conf");
+		assert!(s.starts_with(&format!("{RESET}{c}f.conf ", c = t.file)));
+		assert!(s.contains(&format!("{RESET}{c}not found{RESET}", c = t.error)));
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_display_os_error() {
+		let e = io_error(io::Error::from_raw_os_error(13), None);
+		assert_eq!(
+			Plain(&e).to_string(),
+			concat!(
+				"error:\tf.conf permission denied (os error 13)\n",
+				" ╧ help: check the permissions of the file and of its parent directory (`ls -ld`)",
+			)
+		);
+		assert_eq!(e.help(), help(&e.error));
 	}
 }
@@ -1,4 +1,5 @@
 mod display;
+pub(crate) use display::{display_error, help};
 
 use std::error::Error;
 use std::io;
@@ -6,7 +7,7 @@ use std::io;
 pub struct IoError {
 	pub file: String,
 	pub error: io::Error,
-	pub help: Option<String>,
+	pub help: Option<String>, // replaces the help deduced from the kind of `error`
 	pub source_file: Option<String>,
 	pub source: Option<Box<dyn Error + Send + Sync>>,
 }
@@ -23,12 +24,12 @@ impl Error for IoError {
 ///
 /// * `file` - file name
 /// * `error` - io error
-/// * `h:help` - help message   |optional|
+/// * `h:help` - help message   |optional| (deduced from the kind of `error` if not given)
 /// * `s:source` - source error |optional|
 ///
 /// optional don't need to be defined (don't even need `None`)
 ///
-/// but they must be in order (for now)
+/// they can be given in any order, but only once
 ///
 /// # Example
 ///
@@ -71,13 +72,34 @@ impl Error for IoError {
 macro_rules! ioe {
 	(@some_or_none) => { None };
 	(@some_or_none $entity:expr) => { Some($entity) };
-    ($file:expr, $error:expr $(, h:$help:expr)? $(, s:$source:expr)?$(,)?) => {
+	(@parse ($file:expr, $error:expr) [($($help:expr)?) ($($source:expr)?)]) => {
 		$crate::io::IoError {
 			file: $file.into(),
 			error: $error.into(),
-			help: ioe!(@some_or_none $($help.into())?),
+			help: $crate::ioe!(@some_or_none $($help.into())?),
 			source_file: Some(file!().to_string()),
-			source: ioe!(@some_or_none $(Box::new($source))?),
+			source: $crate::ioe!(@some_or_none $(Box::new($source))?),
 		}
-    };
+	};
+	(@parse $p:tt [() $s:tt] h:$help:expr $(, $($rest:tt)*)?) => {
+		$crate::ioe!(@parse $p [($help) $s] $($($rest)*)?)
+	};
+	(@parse $p:tt [$h:tt ()] s:$source:expr $(, $($rest:tt)*)?) => {
+		$crate::ioe!(@parse $p [$h ($source)] $($($rest)*)?)
+	};
+	(@parse $p:tt [($($h:tt)+) $s:tt] h: $($rest:tt)*) => {
+		compile_error!("`h:` (help) is given more than once to `ioe!`")
+	};
+	(@parse $p:tt [$h:tt ($($s:tt)+)] s: $($rest:tt)*) => {
+		compile_error!("`s:` (source) is given more than once to `ioe!`")
+	};
+	(@parse $p:tt $slot:tt $($rest:tt)*) => {
+		compile_error!(concat!(
+			"unexpected `", stringify!($($rest)*), "` in `ioe!`, ",
+			"the optional arguments are `h:help` and `s:source`"
+		))
+	};
+	($file:expr, $error:expr $(, $($arg:tt)*)?) => {
+		$crate::ioe!(@parse ($file, $error) [() ()] $($($arg)*)?)
+	};
 }
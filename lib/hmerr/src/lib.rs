@@ -6,7 +6,23 @@ pub mod standard;
 pub use standard::StandardError;
 pub mod io;
 pub use io::IoError;
+pub mod diagnostic;
+#[doc(hidden)]
+pub use diagnostic::AsDynError as __AsDynError;
+pub use diagnostic::{Diagnostic, Severity};
+pub mod emitter;
+pub use emitter::Emitter;
+pub mod explain;
+pub mod export;
 pub mod parse;
 pub use parse::ParseFileError;
+pub mod report;
+pub use report::{Context, Report};
+pub mod source;
+pub mod suggestion;
+pub use suggestion::Suggestion;
+
+#[cfg(feature = "derive")]
+pub use hmerr_derive::HmError;
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
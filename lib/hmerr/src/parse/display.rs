@@ -1,95 +1,32 @@
-use super::{Line, ParseFileError, Wrong};
+use super::{ParseFileError, Wrong};
 
-use crate::display::{
-	LINT_COLOR, LINT_SIGN, SIDE, SIDE_PADDING_SIGN, SIDE_SIGN, SOURCE_SIDE_SIGN, SPACE, TAB, write,
-};
+use crate::diagnostic;
+use crate::display;
 
 use std::fmt::{Debug, Display};
 use std::ops::Range;
 
 impl Display for ParseFileError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		let index = self.line.as_ref().and_then(|l| l.index);
-		let padding = write::padding(self.source.as_ref(), index);
-
-		write::error(f, &self.error)?;
-		if !(self.line.is_none() && self.file.is_none()) {
-			write::file(f, &padding, self.file.as_deref(), index)?;
-		}
-		w_line(f, &padding, self.line.as_ref(), self.source.is_some())?;
-		if !(self.line.is_none() && self.file.is_none() && self.help.is_none()) {
-			write::help(f, &padding, self.help.as_deref())?;
-		}
-		write::source_file(f, &padding, self.source_file.as_deref())?;
-		write::source(f, self.source.as_ref())?;
-
-		Ok(())
+		diagnostic::render(f, self)
 	}
 }
 
 impl Debug for ParseFileError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "\x1b[2K\r{self}")
-	}
-}
-
-pub fn w_line(
-	f: &mut std::fmt::Formatter<'_>,
-	padding: &str,
-	line: Option<&Line>,
-	source: bool,
-) -> std::fmt::Result {
-	let Some(line) = line else {
-		return Ok(());
-	};
-	let content = line.line.replace("\t", TAB).replace(" ", SPACE);
-
-	writeln!(f, "\n{padding}{SIDE_PADDING_SIGN}")?;
-
-	if let Some(index) = line.index {
-		if source {
-			write!(f, "{SOURCE_SIDE_SIGN}")?;
-		}
-		writeln!(f, "{SIDE}{index} {SIDE_SIGN}{content}")?;
-	} else {
-		writeln!(f, "{padding}{SIDE_SIGN}{content}")?;
+		write!(f, "{clear}{self}", clear = display::clear_line())
 	}
-
-	w_lint(f, padding, &line.line, &line.wrong)?;
-
-	write!(f, "{padding}{SIDE_PADDING_SIGN}")
-}
-
-fn w_lint(
-	f: &mut std::fmt::Formatter<'_>,
-	padding: &str,
-	line: &str,
-	wrong: &Vec<Wrong>,
-) -> std::fmt::Result {
-	let r = construct_range(wrong, line);
-	if r.is_empty() {
-		return Ok(());
-	}
-
-	let mut s = String::with_capacity(r.last().unwrap().end);
-	let mut start = 0;
-	let lint_sign = LINT_SIGN.to_string();
-	for i in r {
-		s.push_str(&" ".repeat(i.start - start));
-		s.push_str(&lint_sign.repeat(i.end - i.start));
-		start = i.end;
-	}
-	writeln!(f, "{padding}{SIDE_SIGN}{LINT_COLOR}{s}\x1b[0m")?;
-
-	Ok(())
 }
 
-fn construct_range(wrong: &Vec<Wrong>, line: &str) -> Vec<Range<usize>> {
+pub(super) fn construct_range(wrong: &Vec<Wrong>, line: &str) -> Vec<Range<usize>> {
 	let mut r: Vec<Range<usize>> = Vec::new();
 	for w in wrong {
 		match w {
 			Wrong::Bit((start, end)) => {
-				r.push(*start..*start + *end);
+				r.push(*start..start.saturating_add(*end));
+			}
+			Wrong::Label(l) => {
+				r.push(l.range.clone());
 			}
 			Wrong::Str(s) => {
 				if s.is_empty() {
@@ -115,7 +52,7 @@ fn simplifly(r: &mut Vec<Range<usize>>) {
 		return;
 	}
 
-	r.sort_by(|a, b| a.start.cmp(&b.start));
+	r.sort_by_key(|r| r.start);
 	let mut i = 0;
 	while i < r.len() - 1 {
 		if r[i].end >= r[i + 1].start {
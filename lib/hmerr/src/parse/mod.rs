@@ -1,13 +1,22 @@
 mod display;
+mod snippet;
+pub use snippet::{Highlight, Snippet};
+
+use crate::diagnostic::Label;
+use crate::source;
+use crate::suggestion::Suggestion;
 
 use std::error::Error;
 
 #[derive(Default)]
 pub struct ParseFileError {
 	pub error: String,
+	pub code: Option<String>, // stable code, see `explain`
 	pub help: Option<String>,
 	pub file: Option<String>,
 	pub line: Option<Line>,
+	pub snippet: Option<Snippet>, // rendered instead of `line` when set
+	pub suggestion: Vec<Suggestion>,
 	pub source_file: Option<String>,
 	pub source: Option<Box<dyn Error + Send + Sync>>,
 }
@@ -23,6 +32,7 @@ pub struct Line {
 pub enum Wrong {
 	Bit((usize, usize)), // if bit from idx 42 to 45, then start = 42, end = 3
 	Str(String),
+	Label(Label), // range with its own message, rendered beneath the line
 }
 
 impl Error for ParseFileError {
@@ -43,6 +53,12 @@ impl From<&str> for Wrong {
 	}
 }
 
+impl From<Label> for Wrong {
+	fn from(l: Label) -> Self {
+		Self::Label(l)
+	}
+}
+
 impl From<(usize, usize)> for Wrong {
 	fn from((start, end): (usize, usize)) -> Self {
 		Self::Bit((start, end))
@@ -60,13 +76,95 @@ impl ParseFileError {
 	) -> Self {
 		Self {
 			error: error.into(),
+			code: None,
 			file: file.into(),
 			line,
+			snippet: None,
+			suggestion: Vec::new(),
 			help: help.into(),
 			source_file: source_file.into(),
 			source,
 		}
 	}
+
+	/// stable code shown in the header, `error[SPOF0003]:`
+	pub fn with_code(mut self, code: impl Into<String>) -> Self {
+		self.code = Some(code.into());
+		self
+	}
+
+	/// show several lines of the file instead of `line`
+	pub fn with_snippet(mut self, snippet: Snippet) -> Self {
+		self.snippet = Some(snippet);
+		self
+	}
+
+	/// add a fix rendered under the help, see `suggestion::apply` to apply it
+	pub fn with_suggestion(mut self, suggestion: impl Into<Suggestion>) -> Self {
+		self.suggestion.push(suggestion.into());
+		self
+	}
+
+	/// `error` at the byte range `column` of the line `index` (0 based) of `file`
+	///
+	/// the line is read from `source::cache()` (or from disk),
+	/// it is left empty (and nothing is highlighted) if the file cannot be read
+	///
+	/// ```
+	/// use hmerr::ParseFileError;
+	/// use hmerr::source;
+	///
+	/// source::cache().insert("config.toml", "[server]\nport = \"eighty\"\nhost = \"localhost\"");
+	///
+	/// let e = ParseFileError::at("expected an integer", "config.toml", 1, 7..15).with_context(1);
+	/// assert_eq!(e.line.unwrap().line, "port = \"eighty\"");
+	/// assert_eq!(e.snippet.unwrap().line.len(), 3);
+	/// ```
+	pub fn at(
+		error: impl Into<String>,
+		file: impl Into<String>,
+		index: usize,
+		column: std::ops::Range<usize>,
+	) -> Self {
+		let file = file.into();
+		let line = match source::cache().line(&file, index) {
+			Some(line) => Line::new(line, Some(index), vec![(column.start, column.len()).into()]),
+			None => Line::new(String::new(), Some(index), Vec::new()),
+		};
+
+		Self {
+			error: error.into(),
+			line: Some(line),
+			file: Some(file),
+			..Default::default()
+		}
+	}
+
+	/// show `context` lines before and after `line`, read from `source::cache()` (or from disk)
+	///
+	/// nothing changes without a file, a line index or a readable file
+	pub fn with_context(self, context: usize) -> Self {
+		let (Some(file), Some(line)) = (&self.file, &self.line) else {
+			return self;
+		};
+		let (Some(index), Some(content)) = (line.index, source::cache().get(file)) else {
+			return self;
+		};
+
+		let highlight = line
+			.labels()
+			.into_iter()
+			.map(|l| Highlight {
+				start: (index, l.range.start),
+				end: (index, l.range.end),
+				message: l.message,
+				primary: l.primary,
+			})
+			.collect::<Vec<_>>();
+		let snippet = Snippet::around(&content, index..=index, highlight, context);
+
+		self.with_snippet(snippet)
+	}
 }
 
 impl Line {
@@ -77,6 +175,32 @@ impl Line {
 			wrong,
 		}
 	}
+
+	/// byte ranges of `line` highlighted by `wrong`, sorted and merged
+	pub fn range(&self) -> Vec<std::ops::Range<usize>> {
+		display::construct_range(&self.wrong, &self.line)
+	}
+
+	/// `Bit` and `Str` of `wrong` as merged primary labels without message,
+	/// along with every `Wrong::Label`, sorted by start
+	pub fn labels(&self) -> Vec<Label> {
+		let (label, plain): (Vec<_>, Vec<_>) = self
+			.wrong
+			.iter()
+			.cloned()
+			.partition(|w| matches!(w, Wrong::Label(_)));
+
+		let mut label: Vec<Label> = display::construct_range(&plain, &self.line)
+			.into_iter()
+			.map(Label::from)
+			.chain(label.into_iter().filter_map(|w| match w {
+				Wrong::Label(l) => Some(l),
+				_ => None,
+			}))
+			.collect();
+		label.sort_by_key(|l| l.range.start);
+		label
+	}
 }
 
 /// ParseFileError macro
@@ -88,10 +212,11 @@ impl Line {
 /// * `f:file` - file name      |optional|
 /// * `l:line` - line (can be generated with `ple!`) |optional|
 /// * `s:source` - source error |optional|
+/// * `c:code` - stable code, see `explain` |optional|
 ///
 /// optional don't need to be defined (don't even need `None`)
 ///
-/// but they must be in order (for now)
+/// they can be given in any order, but only once
 ///
 /// # Example
 ///
@@ -138,9 +263,10 @@ impl Line {
 /// ```
 #[macro_export]
 macro_rules! pfe {
-    ($error:expr $(, h:$help:expr)? $(, f:$file:expr)? $(, l:$line:expr)? $(, s:$source:expr)?$(,)?) => {
+	(@parse ($error:expr) [($($help:expr)?) ($($file:expr)?) ($($line:expr)?) ($($source:expr)?) ($($code:expr)?)]) => {
 		Err(#[allow(clippy::needless_update)] $crate::parse::ParseFileError {
 			error: $error.into(),
+			$(code: Some($code.into()),)?
 			$(file: Some($file.into()),)?
 			$(line: Some($line),)?
 			$(help: Some($help.into()),)?
@@ -148,7 +274,46 @@ macro_rules! pfe {
 			$(source: Some(Box::new($source)),)?
 			..Default::default()
 		})
-    };
+	};
+	(@parse $p:tt [() $f:tt $l:tt $s:tt $c:tt] h:$help:expr $(, $($rest:tt)*)?) => {
+		$crate::pfe!(@parse $p [($help) $f $l $s $c] $($($rest)*)?)
+	};
+	(@parse $p:tt [$h:tt () $l:tt $s:tt $c:tt] f:$file:expr $(, $($rest:tt)*)?) => {
+		$crate::pfe!(@parse $p [$h ($file) $l $s $c] $($($rest)*)?)
+	};
+	(@parse $p:tt [$h:tt $f:tt () $s:tt $c:tt] l:$line:expr $(, $($rest:tt)*)?) => {
+		$crate::pfe!(@parse $p [$h $f ($line) $s $c] $($($rest)*)?)
+	};
+	(@parse $p:tt [$h:tt $f:tt $l:tt () $c:tt] s:$source:expr $(, $($rest:tt)*)?) => {
+		$crate::pfe!(@parse $p [$h $f $l ($source) $c] $($($rest)*)?)
+	};
+	(@parse $p:tt [$h:tt $f:tt $l:tt $s:tt ()] c:$code:expr $(, $($rest:tt)*)?) => {
+		$crate::pfe!(@parse $p [$h $f $l $s ($code)] $($($rest)*)?)
+	};
+	(@parse $p:tt [($($h:tt)+) $f:tt $l:tt $s:tt $c:tt] h: $($rest:tt)*) => {
+		compile_error!("`h:` (help) is given more than once to `pfe!`")
+	};
+	(@parse $p:tt [$h:tt ($($f:tt)+) $l:tt $s:tt $c:tt] f: $($rest:tt)*) => {
+		compile_error!("`f:` (file) is given more than once to `pfe!`")
+	};
+	(@parse $p:tt [$h:tt $f:tt ($($l:tt)+) $s:tt $c:tt] l: $($rest:tt)*) => {
+		compile_error!("`l:` (line) is given more than once to `pfe!`")
+	};
+	(@parse $p:tt [$h:tt $f:tt $l:tt ($($s:tt)+) $c:tt] s: $($rest:tt)*) => {
+		compile_error!("`s:` (source) is given more than once to `pfe!`")
+	};
+	(@parse $p:tt [$h:tt $f:tt $l:tt $s:tt ($($c:tt)+)] c: $($rest:tt)*) => {
+		compile_error!("`c:` (code) is given more than once to `pfe!`")
+	};
+	(@parse $p:tt $slot:tt $($rest:tt)*) => {
+		compile_error!(concat!(
+			"unexpected `", stringify!($($rest)*), "` in `pfe!`, ",
+			"the optional arguments are `h:help`, `f:file`, `l:line`, `s:source` and `c:code`"
+		))
+	};
+	($error:expr $(, $($arg:tt)*)?) => {
+		$crate::pfe!(@parse ($error) [() () () () ()] $($($arg)*)?)
+	};
 }
 
 /// Line macro
@@ -161,7 +326,7 @@ macro_rules! pfe {
 ///
 /// optional don't need to be defined (don't even need `None`)
 ///
-/// but they must be in order (for now)
+/// they can be given in any order, but only once
 ///
 /// # Example
 ///
@@ -184,13 +349,21 @@ macro_rules! pfe {
 /// assert_eq!(l.line, "John 42");
 /// assert_eq!(l.index, None);
 ///
-/// /* does not compile for now
 /// let l = ple!("John 42", w:pwe!("42"), i:5);
-/// */
+/// assert_eq!(l.line, "John 42");
+/// assert_eq!(l.index, Some(5));
+/// ```
+///
+/// an argument given twice does not compile:
+///
+/// ```compile_fail
+/// use hmerr::ple;
+///
+/// let l = ple!("John 42", i:5, i:6); // `i:` (index) is given more than once to `ple!`
 /// ```
 #[macro_export]
 macro_rules! ple {
-	($line:expr $(, i:$index:expr)? $(, w:$wrong:expr)?$(,)?) => {
+	(@parse ($line:expr) [($($index:expr)?) ($($wrong:expr)?)]) => {
 		{
 			#[allow(clippy::needless_update)] $crate::parse::Line {
 				line: $line.into(),
@@ -200,6 +373,27 @@ macro_rules! ple {
 			}
 		}
 	};
+	(@parse $p:tt [() $w:tt] i:$index:expr $(, $($rest:tt)*)?) => {
+		$crate::ple!(@parse $p [($index) $w] $($($rest)*)?)
+	};
+	(@parse $p:tt [$i:tt ()] w:$wrong:expr $(, $($rest:tt)*)?) => {
+		$crate::ple!(@parse $p [$i ($wrong)] $($($rest)*)?)
+	};
+	(@parse $p:tt [($($i:tt)+) $w:tt] i: $($rest:tt)*) => {
+		compile_error!("`i:` (index) is given more than once to `ple!`")
+	};
+	(@parse $p:tt [$i:tt ($($w:tt)+)] w: $($rest:tt)*) => {
+		compile_error!("`w:` (wrong) is given more than once to `ple!`")
+	};
+	(@parse $p:tt $slot:tt $($rest:tt)*) => {
+		compile_error!(concat!(
+			"unexpected `", stringify!($($rest)*), "` in `ple!`, ",
+			"the optional arguments are `i:index` and `w:wrong`"
+		))
+	};
+	($line:expr $(, $($arg:tt)*)?) => {
+		$crate::ple!(@parse ($line) [() ()] $($($arg)*)?)
+	};
 }
 
 /// Wrong macro
@@ -241,11 +435,18 @@ macro_rules! ple {
 /// // wrong part in "John 42, Will 21" is "42" and index range (17..19)
 /// let w: Vec<Wrong> = pwe!("42", (17, 2));
 ///
+/// // "42" labelled as what is wrong, "John" labelled as context
+/// use hmerr::diagnostic::Label;
+/// let w: Vec<Wrong> = pwe!(
+///     Label::primary(5..7, "expected a name"),
+///     Label::secondary(0..4, "first name"),
+/// );
+///
 /// // no limit on how many you can have
 /// ```
 #[macro_export]
 macro_rules! pwe {
-	($($wrong:expr),*) => {
+	($($wrong:expr),* $(,)?) => {
 		vec![$($wrong.into()),*]
 	};
 }
@@ -0,0 +1,139 @@
+/// several consecutive lines of a file, with highlights that can span lines
+///
+/// ```
+/// use hmerr::parse::{Highlight, Snippet};
+///
+/// let content = "a 1\nblock {\n\tb 2\n\tc 3\nd 4\ne 5";
+///
+/// // from the `{` of line 1 to the end of line 3, with 1 line of context
+/// let s = Snippet::new(content, vec![Highlight::primary((1, 6), (3, 4), "unterminated block")], 1);
+/// assert_eq!(s.first, 0);
+/// assert_eq!(s.line.len(), 5);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Snippet {
+	pub line: Vec<String>, // every line shown, context included
+	pub first: usize,      // index of `line[0]` in the file
+	pub highlight: Vec<Highlight>,
+}
+
+/// `start` and `end` are `(line index, byte column)` in the file, `end` is exclusive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+	pub start: (usize, usize),
+	pub end: (usize, usize),
+	pub message: Option<String>,
+	pub primary: bool,
+}
+
+impl Snippet {
+	/// lines of `content` covered by `highlight`, with `context` lines before and after
+	pub fn new(content: &str, highlight: Vec<Highlight>, context: usize) -> Self {
+		let first = highlight.iter().map(|h| h.start.0).min().unwrap_or(0);
+		let last = highlight.iter().map(|h| h.end.0).max().unwrap_or(0);
+
+		Self::around(content, first..=last, highlight, context)
+	}
+
+	/// lines `line` of `content`, with `context` lines before and after
+	pub(super) fn around(
+		content: &str,
+		line: std::ops::RangeInclusive<usize>,
+		highlight: Vec<Highlight>,
+		context: usize,
+	) -> Self {
+		let (first, last) = line.into_inner();
+		let first = first.saturating_sub(context);
+
+		Self {
+			line: content
+				.lines()
+				.skip(first)
+				.take(last.saturating_add(context).saturating_add(1) - first)
+				.map(String::from)
+				.collect(),
+			first,
+			highlight,
+		}
+	}
+
+	pub fn from_lines(first: usize, line: Vec<String>, highlight: Vec<Highlight>) -> Self {
+		Self {
+			line,
+			first,
+			highlight,
+		}
+	}
+
+	/// index of the last line shown
+	pub fn last(&self) -> usize {
+		self.first + self.line.len().saturating_sub(1)
+	}
+
+	/// first primary highlight, the first highlight if none is primary
+	pub fn primary(&self) -> Option<&Highlight> {
+		self.highlight
+			.iter()
+			.find(|h| h.primary)
+			.or(self.highlight.first())
+	}
+}
+
+impl Highlight {
+	pub fn primary(start: (usize, usize), end: (usize, usize), message: impl Into<String>) -> Self {
+		Self {
+			start,
+			end,
+			message: Some(message.into()),
+			primary: true,
+		}
+	}
+
+	pub fn secondary(
+		start: (usize, usize),
+		end: (usize, usize),
+		message: impl Into<String>,
+	) -> Self {
+		Self {
+			start,
+			end,
+			message: Some(message.into()),
+			primary: false,
+		}
+	}
+
+	pub fn is_multiline(&self) -> bool {
+		self.start.0 != self.end.0
+	}
+}
+
+impl From<((usize, usize), (usize, usize))> for Highlight {
+	fn from((start, end): ((usize, usize), (usize, usize))) -> Self {
+		Self {
+			start,
+			end,
+			message: None,
+			primary: true,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_snippet_new() {
+		let content = "0\n1\n2\n3\n4\n5";
+
+		let s = Snippet::new(content, vec![((2, 0), (3, 1)).into()], 1);
+		assert_eq!(s.first, 1);
+		assert_eq!(s.line, vec!["1", "2", "3", "4"]);
+		assert_eq!(s.last(), 4);
+
+		// context is clipped to the file
+		let s = Snippet::new(content, vec![((0, 0), (5, 1)).into()], 3);
+		assert_eq!(s.first, 0);
+		assert_eq!(s.line.len(), 6);
+	}
+}
@@ -0,0 +1,128 @@
+use super::{Annotated, DynDiagnostic, Report};
+
+use crate::diagnostic::{self, Diagnostic, Label, Location, Severity, Snapshot, as_diagnostic};
+use crate::display;
+use crate::suggestion::Suggestion;
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
+/// an error that is not from hmerr, in the hmerr layout
+///
+/// as its `Diagnostic` if its `Display` is rendered by hmerr (see `Snapshot::of`),
+/// with its message as the only thing known otherwise
+pub(crate) struct Foreign<'a> {
+	error: &'a (dyn Error + 'static),
+	snapshot: Option<Snapshot>,
+}
+
+impl<'a> Foreign<'a> {
+	pub fn new(error: &'a (dyn Error + 'static)) -> Self {
+		Self {
+			error,
+			snapshot: Snapshot::of(error),
+		}
+	}
+}
+
+impl Error for Foreign<'_> {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		self.error.source()
+	}
+}
+
+impl Diagnostic for Foreign<'_> {
+	fn severity(&self) -> Severity {
+		self.snapshot
+			.as_ref()
+			.map_or(Severity::Error, |s| s.severity)
+	}
+
+	fn message(&self) -> Cow<'_, str> {
+		match &self.snapshot {
+			Some(s) => Cow::Borrowed(&s.message),
+			None => Cow::Owned(self.error.to_string()),
+		}
+	}
+
+	fn code(&self) -> Option<&str> {
+		self.snapshot.as_ref()?.code.as_deref()
+	}
+
+	fn help(&self) -> Option<&str> {
+		self.snapshot.as_ref()?.help.as_deref()
+	}
+
+	fn location(&self) -> Option<Location<'_>> {
+		self.snapshot.as_ref()?.location()
+	}
+
+	fn labels(&self) -> Vec<Label> {
+		self.snapshot
+			.as_ref()
+			.map(|s| s.labels.clone())
+			.unwrap_or_default()
+	}
+
+	fn suggestions(&self) -> &[Suggestion] {
+		self.snapshot.as_ref().map_or(&[], |s| &s.suggestions)
+	}
+
+	fn source_file(&self) -> Option<&str> {
+		self.snapshot.as_ref()?.source_file.as_deref()
+	}
+}
+
+impl Display for Foreign<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		diagnostic::render(f, self)
+	}
+}
+
+impl Debug for Foreign<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		Display::fmt(self, f)
+	}
+}
+
+impl Display for Report {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let e: &(dyn Error + 'static) = &*self.0;
+		if as_diagnostic(e).is_some() {
+			Display::fmt(e, f)
+		} else {
+			Display::fmt(&Foreign::new(e), f)
+		}
+	}
+}
+
+impl Debug for Report {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{clear}{self}", clear = display::clear_line())
+	}
+}
+
+impl Display for Annotated {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		diagnostic::render(f, self)
+	}
+}
+
+impl Debug for Annotated {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{clear}{self}", clear = display::clear_line())
+	}
+}
+
+impl Display for DynDiagnostic {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		diagnostic::render(f, self)
+	}
+}
+
+impl Debug for DynDiagnostic {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{clear}{self}", clear = display::clear_line())
+	}
+}
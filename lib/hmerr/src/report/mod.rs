@@ -0,0 +1,615 @@
+mod display;
+pub(crate) use display::Foreign;
+
+use crate::diagnostic::{Diagnostic, Label, Location, Severity, Snapshot};
+use crate::suggestion::Suggestion;
+use crate::{GenericError, IoError, ParseFileError, StandardError};
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::sync::OnceLock;
+
+/// any error, `Send + Sync`, rendered in the hmerr layout
+///
+/// built with `?` from any error, context is added with `Context`:
+///
+/// ```
+/// use hmerr::{Context, Report};
+///
+/// fn port(s: &str) -> Result<u16, Report> {
+///     let port = s
+///         .parse::<u16>()
+///         .context(format!("invalid port `{s}`"))
+///         .help("a port is a number from 0 to 65535")?;
+///     Ok(port)
+/// }
+///
+/// let e = port("http").unwrap_err();
+/// assert!(e.downcast_ref::<std::num::ParseIntError>().is_some());
+/// ```
+pub struct Report(Box<dyn Error + Send + Sync + 'static>);
+
+/// context added on any `Result`, see `Report`
+pub trait Context<T> {
+	/// wrap the error in a new one with `message`, the error becomes its cause
+	fn context(self, message: impl Into<String>) -> Result<T, Report>;
+
+	/// set the help of the error
+	fn help(self, help: impl Into<String>) -> Result<T, Report>;
+
+	/// set the file the error happened in
+	fn with_file(self, file: impl Into<String>) -> Result<T, Report>;
+}
+
+/// an error that is not from hmerr, with the help and file it cannot hold
+///
+/// rendered as the `Diagnostic` of `error` if it has one (see `Snapshot::of`),
+/// with the message of `error` otherwise, and the causes of `error` as its causes
+pub(crate) struct Annotated {
+	pub error: Box<dyn Error + Send + Sync>,
+	pub snapshot: OnceLock<Option<Snapshot>>, // reset when `error` is borrowed mutably
+	pub help: Option<String>,
+	pub file: Option<String>,
+	pub source_file: Option<String>,
+}
+
+/// a `Diagnostic` that is not from hmerr, see `Report::from_diagnostic`
+pub(crate) struct DynDiagnostic(pub Box<dyn Diagnostic + Send + Sync>);
+
+impl Report {
+	pub fn new(error: impl Error + Send + Sync + 'static) -> Self {
+		Self(Box::new(error))
+	}
+
+	/// keep `diagnostic` as a `Diagnostic` (its message, code, labels and location),
+	/// for a type that is not from hmerr and does not render itself with `diagnostic::render`
+	///
+	/// ```
+	/// # use std::borrow::Cow;
+	/// # use std::error::Error;
+	/// # use std::fmt::{Debug, Display, Formatter};
+	/// use hmerr::diagnostic::{Diagnostic, Severity};
+	/// use hmerr::display::Plain;
+	/// use hmerr::Report;
+	///
+	/// #[derive(Debug)]
+	/// struct Deprecated(&'static str);
+	///
+	/// impl Display for Deprecated {
+	///     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+	///         write!(f, "`{}` is deprecated", self.0)
+	///     }
+	/// }
+	///
+	/// impl Error for Deprecated {}
+	///
+	/// impl Diagnostic for Deprecated {
+	///     fn severity(&self) -> Severity {
+	///         Severity::Warning
+	///     }
+	///
+	///     fn message(&self) -> Cow<'_, str> {
+	///         self.to_string().into()
+	///     }
+	///
+	///     fn code(&self) -> Option<&str> {
+	///         Some("OBJ0042")
+	///     }
+	/// }
+	///
+	/// let e = Report::from_diagnostic(Deprecated("vt")).help("use `vn`");
+	/// assert!(Plain(&e).to_string().starts_with("warning[OBJ0042]:\t`vt` is deprecated\n"));
+	/// assert!(e.is::<Deprecated>());
+	/// ```
+	pub fn from_diagnostic(diagnostic: impl Diagnostic + Send + Sync + 'static) -> Self {
+		Self::new(DynDiagnostic(Box::new(diagnostic)))
+	}
+
+	/// wrap the error in a `GenericError` with `message`, the error becomes its cause
+	#[track_caller]
+	pub fn context(self, message: impl Into<String>) -> Self {
+		Self::new(GenericError {
+			error: message.into(),
+			help: None,
+			source_file: Some(caller()),
+			source: Some(self.0),
+		})
+	}
+
+	/// set the help of the error (replacing the previous one)
+	#[track_caller]
+	pub fn help(mut self, help: impl Into<String>) -> Self {
+		let help = Some(help.into());
+		let e = &mut self.0;
+		if let Some(e) = e.downcast_mut::<GenericError>() {
+			e.help = help;
+		} else if let Some(e) = e.downcast_mut::<ParseFileError>() {
+			e.help = help;
+		} else if let Some(e) = e.downcast_mut::<StandardError>() {
+			e.help = help;
+		} else if let Some(e) = e.downcast_mut::<IoError>() {
+			e.help = help;
+		} else if let Some(e) = e.downcast_mut::<Annotated>() {
+			e.help = help;
+		} else {
+			return Self::new(Annotated {
+				help,
+				..Annotated::new(self.0, caller())
+			});
+		}
+		self
+	}
+
+	/// set the file the error happened in
+	///
+	/// a `GenericError` becomes a `ParseFileError`, an `io::Error` an `IoError`,
+	/// a `StandardError` a `ParseFileError` with what was expected and got in its message
+	#[track_caller]
+	pub fn with_file(mut self, file: impl Into<String>) -> Self {
+		let file = file.into();
+		let e = &mut self.0;
+		if let Some(e) = e.downcast_mut::<ParseFileError>() {
+			e.file = Some(file);
+		} else if let Some(e) = e.downcast_mut::<IoError>() {
+			e.file = file;
+		} else if let Some(e) = e.downcast_mut::<Annotated>() {
+			e.file = Some(file);
+		} else if self.0.is::<GenericError>() {
+			let Ok(e) = self.0.downcast::<GenericError>() else {
+				unreachable!("checked just above");
+			};
+			return Self::new(ParseFileError {
+				error: e.error,
+				help: e.help,
+				file: Some(file),
+				source_file: e.source_file,
+				source: e.source,
+				..Default::default()
+			});
+		} else if self.0.is::<StandardError>() {
+			let Ok(e) = self.0.downcast::<StandardError>() else {
+				unreachable!("checked just above");
+			};
+			let or_nothing = |s: String| {
+				if s.is_empty() {
+					"nothing".to_string()
+				} else {
+					s
+				}
+			};
+			return Self::new(ParseFileError {
+				error: format!(
+					"{error}: expected {expected}, got {got}",
+					error = e.error,
+					expected = or_nothing(e.expected),
+					got = or_nothing(e.got)
+				),
+				help: e.help,
+				file: Some(file),
+				source_file: e.source_file,
+				source: e.source,
+				..Default::default()
+			});
+		} else if self.0.is::<std::io::Error>() {
+			let Ok(e) = self.0.downcast::<std::io::Error>() else {
+				unreachable!("checked just above");
+			};
+			return Self::new(IoError {
+				file,
+				error: *e,
+				help: None,
+				source_file: Some(caller()),
+				source: None,
+			});
+		} else {
+			return Self::new(Annotated {
+				file: Some(file),
+				..Annotated::new(self.0, caller())
+			});
+		}
+		self
+	}
+
+	pub fn is<E: Error + 'static>(&self) -> bool {
+		self.downcast_ref::<E>().is_some()
+	}
+
+	/// the error if it is an `E`, even once given a help or a file
+	pub fn downcast<E: Error + 'static>(self) -> Result<E, Self> {
+		if !inner(&*self.0).is::<E>() {
+			return Err(self);
+		}
+
+		let mut e = self.0;
+		loop {
+			e = match e.downcast::<Annotated>() {
+				Ok(a) => a.error,
+				Err(e) => match e.downcast::<DynDiagnostic>() {
+					Ok(d) => d.0,
+					Err(e) => match e.downcast::<E>() {
+						Ok(e) => return Ok(*e),
+						Err(_) => unreachable!("checked by `inner`"),
+					},
+				},
+			};
+		}
+	}
+
+	/// the first error of the chain that is an `E`
+	pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+		self.chain().find_map(|e| inner(e).downcast_ref::<E>())
+	}
+
+	pub fn downcast_mut<E: Error + 'static>(&mut self) -> Option<&mut E> {
+		let mut e: &mut (dyn Error + Send + Sync + 'static) = &mut *self.0;
+		loop {
+			if e.is::<Annotated>() {
+				let a = e.downcast_mut::<Annotated>()?;
+				a.snapshot = OnceLock::new(); // `E` may be changed
+				e = &mut *a.error;
+			} else if e.is::<DynDiagnostic>() {
+				e = &mut *e.downcast_mut::<DynDiagnostic>()?.0;
+			} else {
+				return e.downcast_mut::<E>();
+			}
+		}
+	}
+
+	/// the error, then every error that caused it
+	pub fn chain(&self) -> impl Iterator<Item = &(dyn Error + 'static)> {
+		std::iter::successors(Some(&*self.0 as &(dyn Error + 'static)), |e| (*e).source())
+	}
+
+	pub fn into_inner(self) -> Box<dyn Error + Send + Sync + 'static> {
+		self.0
+	}
+}
+
+/// the error under the wrappers added by `Report`
+fn inner<'a>(e: &'a (dyn Error + 'static)) -> &'a (dyn Error + 'static) {
+	if let Some(a) = e.downcast_ref::<Annotated>() {
+		inner(&*a.error)
+	} else if let Some(d) = e.downcast_ref::<DynDiagnostic>() {
+		&*d.0
+	} else {
+		e
+	}
+}
+
+#[track_caller]
+fn caller() -> String {
+	std::panic::Location::caller().file().to_string()
+}
+
+impl<E: Error + Send + Sync + 'static> From<E> for Report {
+	fn from(error: E) -> Self {
+		Self::new(error)
+	}
+}
+
+impl From<Report> for Box<dyn Error + Send + Sync + 'static> {
+	fn from(report: Report) -> Self {
+		report.0
+	}
+}
+
+impl From<Report> for Box<dyn Error + 'static> {
+	fn from(report: Report) -> Self {
+		report.0
+	}
+}
+
+impl std::ops::Deref for Report {
+	type Target = dyn Error + Send + Sync + 'static;
+
+	fn deref(&self) -> &Self::Target {
+		&*self.0
+	}
+}
+
+impl AsRef<dyn Error + Send + Sync + 'static> for Report {
+	fn as_ref(&self) -> &(dyn Error + Send + Sync + 'static) {
+		&*self.0
+	}
+}
+
+// `match` rather than `map_err`, a closure would hide the caller from `#[track_caller]`
+impl<T, E: Error + Send + Sync + 'static> Context<T> for Result<T, E> {
+	#[track_caller]
+	fn context(self, message: impl Into<String>) -> Result<T, Report> {
+		match self {
+			Ok(v) => Ok(v),
+			Err(e) => Err(Report::new(e).context(message)),
+		}
+	}
+
+	#[track_caller]
+	fn help(self, help: impl Into<String>) -> Result<T, Report> {
+		match self {
+			Ok(v) => Ok(v),
+			Err(e) => Err(Report::new(e).help(help)),
+		}
+	}
+
+	#[track_caller]
+	fn with_file(self, file: impl Into<String>) -> Result<T, Report> {
+		match self {
+			Ok(v) => Ok(v),
+			Err(e) => Err(Report::new(e).with_file(file)),
+		}
+	}
+}
+
+impl<T> Context<T> for Result<T, Report> {
+	#[track_caller]
+	fn context(self, message: impl Into<String>) -> Result<T, Report> {
+		match self {
+			Ok(v) => Ok(v),
+			Err(e) => Err(e.context(message)),
+		}
+	}
+
+	#[track_caller]
+	fn help(self, help: impl Into<String>) -> Result<T, Report> {
+		match self {
+			Ok(v) => Ok(v),
+			Err(e) => Err(e.help(help)),
+		}
+	}
+
+	#[track_caller]
+	fn with_file(self, file: impl Into<String>) -> Result<T, Report> {
+		match self {
+			Ok(v) => Ok(v),
+			Err(e) => Err(e.with_file(file)),
+		}
+	}
+}
+
+impl Annotated {
+	fn new(error: Box<dyn Error + Send + Sync>, source_file: String) -> Self {
+		Self {
+			error,
+			snapshot: OnceLock::new(),
+			help: None,
+			file: None,
+			source_file: Some(source_file),
+		}
+	}
+
+	fn snapshot(&self) -> Option<&Snapshot> {
+		self.snapshot
+			.get_or_init(|| Snapshot::of(&*self.error))
+			.as_ref()
+	}
+}
+
+impl Error for Annotated {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		self.error.source()
+	}
+}
+
+// the help and file of `Annotated` take precedence over the ones of `error`
+impl Diagnostic for Annotated {
+	fn severity(&self) -> Severity {
+		self.snapshot().map_or(Severity::Error, |s| s.severity)
+	}
+
+	fn message(&self) -> Cow<'_, str> {
+		match self.snapshot() {
+			Some(s) => Cow::Borrowed(&s.message),
+			None => Cow::Owned(self.error.to_string()),
+		}
+	}
+
+	fn code(&self) -> Option<&str> {
+		self.snapshot()?.code.as_deref()
+	}
+
+	fn help(&self) -> Option<&str> {
+		self.help
+			.as_deref()
+			.or_else(|| self.snapshot()?.help.as_deref())
+	}
+
+	fn location(&self) -> Option<Location<'_>> {
+		let location = self.snapshot().and_then(Snapshot::location);
+		match self.file.as_deref() {
+			Some(file) => Some(Location {
+				file: Some(file),
+				..location.unwrap_or_default()
+			}),
+			None => location,
+		}
+	}
+
+	fn labels(&self) -> Vec<Label> {
+		self.snapshot()
+			.map(|s| s.labels.clone())
+			.unwrap_or_default()
+	}
+
+	fn suggestions(&self) -> &[Suggestion] {
+		self.snapshot().map_or(&[], |s| &s.suggestions)
+	}
+
+	fn source_file(&self) -> Option<&str> {
+		self.snapshot()
+			.and_then(|s| s.source_file.as_deref())
+			.or(self.source_file.as_deref())
+	}
+}
+
+impl Error for DynDiagnostic {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		self.0.source()
+	}
+}
+
+impl Diagnostic for DynDiagnostic {
+	fn severity(&self) -> Severity {
+		self.0.severity()
+	}
+
+	fn message(&self) -> Cow<'_, str> {
+		self.0.message()
+	}
+
+	fn code(&self) -> Option<&str> {
+		self.0.code()
+	}
+
+	fn help(&self) -> Option<&str> {
+		self.0.help()
+	}
+
+	fn location(&self) -> Option<Location<'_>> {
+		self.0.location()
+	}
+
+	fn labels(&self) -> Vec<Label> {
+		self.0.labels()
+	}
+
+	fn suggestions(&self) -> &[Suggestion] {
+		self.0.suggestions()
+	}
+
+	fn source_file(&self) -> Option<&str> {
+		self.0.source_file()
+	}
+
+	fn caused_by(&self) -> Option<&(dyn Error + 'static)> {
+		self.0.caused_by()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::display::Plain;
+
+	fn parse(s: &str) -> Result<u8, std::num::ParseIntError> {
+		s.parse::<u8>()
+	}
+
+	#[test]
+	fn test_report_send_sync() {
+		fn assert<T: Send + Sync>() {}
+		assert::<Report>();
+	}
+
+	#[test]
+	fn test_report_context() {
+		let e = parse("x")
+			.context("invalid age")
+			.context("could not load profile")
+			.unwrap_err();
+
+		let message: Vec<String> = e.chain().map(|e| Plain(e).to_string()).collect();
+		assert_eq!(message.len(), 3);
+		assert!(message[0].starts_with("error:\tcould not load profile"));
+		assert!(message[1].starts_with("error:\tinvalid age"));
+		assert!(e.downcast_ref::<std::num::ParseIntError>().is_some());
+		assert!(e.is::<GenericError>());
+		// generated where the context was added
+		assert_eq!(
+			e.downcast_ref::<GenericError>()
+				.unwrap()
+				.source_file
+				.as_deref(),
+			Some(file!())
+		);
+
+		let s = Plain(&e).to_string();
+		assert!(s.starts_with("error:\tcould not load profile\n"));
+		assert!(s.contains("invalid age"));
+		assert!(s.ends_with("invalid digit found in string"));
+	}
+
+	#[test]
+	fn test_report_help_file() {
+		// a foreign error keeps its type under the help
+		let e = parse("x")
+			.help("use digits")
+			.with_file("age.txt")
+			.unwrap_err();
+		let s = Plain(&e).to_string();
+		assert!(s.starts_with("error:\tinvalid digit found in string\n"));
+		assert!(s.contains("age.txt"));
+		assert!(s.contains("help: use digits"));
+		assert!(e.downcast::<std::num::ParseIntError>().is_ok());
+
+		// a generic error becomes a parse error
+		let e = Err::<(), _>(GenericError {
+			error: "empty".to_string(),
+			help: None,
+			source_file: None,
+			source: None,
+		})
+		.with_file("age.txt")
+		.help("write an age")
+		.unwrap_err();
+		let e = e.downcast::<ParseFileError>().unwrap();
+		assert_eq!(e.file.as_deref(), Some("age.txt"));
+		assert_eq!(e.help.as_deref(), Some("write an age"));
+
+		let e = Err::<(), _>(std::io::Error::from(std::io::ErrorKind::NotFound))
+			.with_file("age.txt")
+			.unwrap_err();
+		assert_eq!(e.downcast_ref::<IoError>().unwrap().file, "age.txt");
+	}
+
+	#[test]
+	fn test_report_standard_file() {
+		let e = Err::<(), _>(StandardError {
+			error: "bad".to_string(),
+			expected: "a number".to_string(),
+			got: String::new(),
+			help: None,
+			source_file: None,
+			source: None,
+		})
+		.with_file("f.txt")
+		.unwrap_err();
+		let s = Plain(&e).to_string();
+		assert!(s.starts_with("error:\tbad: expected a number, got nothing\n"));
+		assert_eq!(s.matches("error:").count(), 1);
+		assert!(s.contains("f.txt"));
+	}
+
+	/// not from hmerr, with its own text around a rendered error
+	#[derive(Debug)]
+	struct Wrapper(GenericError);
+
+	impl std::fmt::Display for Wrapper {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			write!(f, "loading config failed: {}", self.0)
+		}
+	}
+
+	impl Error for Wrapper {}
+
+	#[test]
+	fn test_report_wrapper() {
+		let wrapper = || {
+			Wrapper(GenericError {
+				error: "missing `port`".to_string(),
+				help: None,
+				source_file: None,
+				source: None,
+			})
+		};
+
+		let s = Plain(Report::new(wrapper())).to_string();
+		assert!(s.contains("loading config failed: "));
+		assert!(s.contains("missing `port`"));
+
+		let e = Err::<(), _>(wrapper()).help("add a port").unwrap_err();
+		let s = Plain(&e).to_string();
+		assert!(s.starts_with("error:\tloading config failed: "));
+		assert!(s.contains("missing `port`"));
+		assert!(s.contains("help: add a port"));
+	}
+}
@@ -0,0 +1,98 @@
+//! content of the files errors point into, so an error can be built from a position only
+//!
+//! a file is looked up in the cache first, then read from disk (and cached)
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// content of files by path
+///
+/// ```
+/// use hmerr::source::SourceCache;
+///
+/// let cache = SourceCache::new();
+/// cache.insert("scene.obj", "v 1 2 3\nvt 1 2\nf 1 2 3");
+/// assert_eq!(cache.line("scene.obj", 1).as_deref(), Some("vt 1 2"));
+/// assert_eq!(cache.line("scene.obj", 42), None);
+/// ```
+#[derive(Debug, Default)]
+pub struct SourceCache {
+	file: RwLock<BTreeMap<String, Arc<str>>>,
+}
+
+impl SourceCache {
+	pub const fn new() -> Self {
+		Self {
+			file: RwLock::new(BTreeMap::new()),
+		}
+	}
+
+	/// use `content` for `path` instead of reading it from disk
+	pub fn insert(&self, path: impl Into<String>, content: impl Into<Arc<str>>) {
+		self.file
+			.write()
+			.unwrap_or_else(|e| e.into_inner())
+			.insert(path.into(), content.into());
+	}
+
+	pub fn remove(&self, path: &str) -> Option<Arc<str>> {
+		self.file
+			.write()
+			.unwrap_or_else(|e| e.into_inner())
+			.remove(path)
+	}
+
+	pub fn clear(&self) {
+		self.file.write().unwrap_or_else(|e| e.into_inner()).clear();
+	}
+
+	/// content of `path`, from the cache or from disk (invalid UTF-8 is replaced)
+	pub fn get(&self, path: &str) -> Option<Arc<str>> {
+		if let Some(content) = self
+			.file
+			.read()
+			.unwrap_or_else(|e| e.into_inner())
+			.get(path)
+		{
+			return Some(Arc::clone(content));
+		}
+
+		let content: Arc<str> = String::from_utf8_lossy(&std::fs::read(path).ok()?).into();
+		self.insert(path, Arc::clone(&content));
+		Some(content)
+	}
+
+	/// line `index` (0 based) of `path`
+	pub fn line(&self, path: &str, index: usize) -> Option<String> {
+		self.get(path)?.lines().nth(index).map(String::from)
+	}
+}
+
+static CACHE: SourceCache = SourceCache::new();
+
+/// cache used by `ParseFileError::at` and `ParseFileError::with_context`
+pub fn cache() -> &'static SourceCache {
+	&CACHE
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_source_cache_disk() {
+		let path = std::env::temp_dir().join(format!("hmerr_source_{}.txt", std::process::id()));
+		std::fs::write(&path, b"first\nsecond\xff\n").unwrap();
+		let path = path.to_str().unwrap();
+
+		let cache = SourceCache::new();
+		assert_eq!(cache.line(path, 1).as_deref(), Some("second\u{fffd}"));
+
+		// cached, even once the file is gone
+		std::fs::remove_file(path).unwrap();
+		assert_eq!(cache.line(path, 0).as_deref(), Some("first"));
+
+		cache.remove(path);
+		assert!(cache.get(path).is_none());
+	}
+}
@@ -1,48 +1,60 @@
 use super::StandardError;
 
-use crate::display::{EMPTY_STR, EXPECTED, GOT, write};
+use crate::display::{self, RESET, Theme, theme, write};
 
 use std::fmt::{Debug, Display};
 
 impl Display for StandardError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		let padding = write::padding(self.source.as_ref(), None);
-
-		write::error(f, &self.error)?;
-		w_expected(f, &padding, &self.expected)?;
-		w_got(f, &padding, &self.got)?;
-		if let Some(help) = &self.help {
-			write::help(f, &padding, Some(help))?;
-		}
-		write::source_file(f, &padding, self.source_file.as_deref())?;
-		write::source(f, self.source.as_ref())?;
-
-		Ok(())
+		display::render(f, |f| {
+			let padding = write::padding(self.source.as_ref(), None);
+
+			write::error(f, &self.error)?;
+			w_expected(f, &padding, &self.expected)?;
+			w_got(f, &padding, &self.got)?;
+			if let Some(help) = &self.help {
+				write::help(f, &padding, Some(help))?;
+			}
+			write::source_file(f, &padding, self.source_file.as_deref())?;
+			write::source(f, self.source.as_ref())?;
+
+			Ok(())
+		})
 	}
 }
 
 impl Debug for StandardError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "\x1b[2K\r{self}")
+		write!(f, "{clear}{self}", clear = display::clear_line())
 	}
 }
 
 fn w_expected(f: &mut std::fmt::Formatter<'_>, padding: &str, expected: &str) -> std::fmt::Result {
+	let t = theme();
 	write!(
 		f,
-		"\n{padding}{EXPECTED}{expected}\x1b[0m",
-		expected = if expected.is_empty() {
-			EMPTY_STR
-		} else {
-			expected
-		},
+		"\n{padding}{RESET}\t{label}expected:{RESET} {color}{expected}{RESET}",
+		label = t.label,
+		color = t.expected,
+		expected = or_nothing(&t, expected),
 	)
 }
 
 fn w_got(f: &mut std::fmt::Formatter<'_>, padding: &str, got: &str) -> std::fmt::Result {
+	let t = theme();
 	write!(
 		f,
-		"\n{padding}{GOT}{got}\x1b[0m",
-		got = if got.is_empty() { EMPTY_STR } else { got },
+		"\n{padding}{RESET}\t{label}got:{RESET}      {color}{got}{RESET}",
+		label = t.label,
+		color = t.got,
+		got = or_nothing(&t, got),
 	)
 }
+
+fn or_nothing(t: &Theme, s: &str) -> String {
+	if s.is_empty() {
+		format!("{label}nothing{RESET}", label = t.label)
+	} else {
+		s.to_string()
+	}
+}
@@ -29,7 +29,7 @@ impl Error for StandardError {
 ///
 /// optional don't need to be defined (don't even need `None`)
 ///
-/// but they must be in order (for now)
+/// they can be given in any order, but only once
 ///
 /// # Example
 ///
@@ -71,14 +71,35 @@ impl Error for StandardError {
 macro_rules! se {
 	(@some_or_none) => { None };
 	(@some_or_none $entity:expr) => { Some($entity) };
-    ($error:expr, $expected:expr, $got:expr $(, h:$help:expr)? $(, s:$source:expr)?$(,)?) => {
+	(@parse ($error:expr, $expected:expr, $got:expr) [($($help:expr)?) ($($source:expr)?)]) => {
 		$crate::standard::StandardError {
 			error: $error.into(),
 			expected: $expected.into(),
 			got: $got.into(),
-			help: se!(@some_or_none $($help.into())?),
+			help: $crate::se!(@some_or_none $($help.into())?),
 			source_file: Some(file!().to_string()),
-			source: se!(@some_or_none $(Box::new($source))?),
+			source: $crate::se!(@some_or_none $(Box::new($source))?),
 		}
-    };
+	};
+	(@parse $p:tt [() $s:tt] h:$help:expr $(, $($rest:tt)*)?) => {
+		$crate::se!(@parse $p [($help) $s] $($($rest)*)?)
+	};
+	(@parse $p:tt [$h:tt ()] s:$source:expr $(, $($rest:tt)*)?) => {
+		$crate::se!(@parse $p [$h ($source)] $($($rest)*)?)
+	};
+	(@parse $p:tt [($($h:tt)+) $s:tt] h: $($rest:tt)*) => {
+		compile_error!("`h:` (help) is given more than once to `se!`")
+	};
+	(@parse $p:tt [$h:tt ($($s:tt)+)] s: $($rest:tt)*) => {
+		compile_error!("`s:` (source) is given more than once to `se!`")
+	};
+	(@parse $p:tt $slot:tt $($rest:tt)*) => {
+		compile_error!(concat!(
+			"unexpected `", stringify!($($rest)*), "` in `se!`, ",
+			"the optional arguments are `h:help` and `s:source`"
+		))
+	};
+	($error:expr, $expected:expr, $got:expr $(, $($arg:tt)*)?) => {
+		$crate::se!(@parse ($error, $expected, $got) [() ()] $($($arg)*)?)
+	};
 }
@@ -0,0 +1,199 @@
+//! fix-it suggestions: changes to the source that would fix an error
+//!
+//! every position is a line index (0 based) and a byte range in that line,
+//! like `parse::Line` and `parse::Highlight`
+
+use crate::GenericError;
+
+use std::ops::Range;
+
+/// one or several edits fixing an error together
+///
+/// ```
+/// use hmerr::suggestion::{Edit, Suggestion};
+///
+/// let s = Suggestion::new("did you mean `vt`?", vec![Edit::replace(1, 0..2, "vt")]);
+/// assert_eq!(s.apply("v 1 2 3\ntv 1 2").unwrap(), "v 1 2 3\nvt 1 2");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+	pub message: Option<String>,
+	pub edit: Vec<Edit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+	pub line: usize,
+	pub range: Range<usize>, // byte range in the line, empty to insert
+	pub text: String,        // empty to delete
+}
+
+impl Suggestion {
+	pub fn new(message: impl Into<String>, edit: Vec<Edit>) -> Self {
+		Self {
+			message: Some(message.into()),
+			edit,
+		}
+	}
+
+	/// `content` with the edits of this suggestion applied
+	pub fn apply(&self, content: &str) -> Result<String, GenericError> {
+		apply(content, std::slice::from_ref(self))
+	}
+}
+
+impl From<Edit> for Suggestion {
+	fn from(edit: Edit) -> Self {
+		Self {
+			message: None,
+			edit: vec![edit],
+		}
+	}
+}
+
+impl Edit {
+	pub fn replace(line: usize, range: Range<usize>, text: impl Into<String>) -> Self {
+		Self {
+			line,
+			range,
+			text: text.into(),
+		}
+	}
+
+	pub fn insert(line: usize, at: usize, text: impl Into<String>) -> Self {
+		Self::replace(line, at..at, text)
+	}
+
+	pub fn delete(line: usize, range: Range<usize>) -> Self {
+		Self::replace(line, range, String::new())
+	}
+}
+
+/// `content` with the edits of every suggestion applied
+///
+/// fails without changing anything if an edit is out of its line,
+/// cuts a character in half, or overlaps another edit
+///
+/// edits inserting at the same position are applied in order
+pub fn apply(content: &str, suggestion: &[Suggestion]) -> Result<String, GenericError> {
+	let start = line_start(content);
+
+	let mut edit = Vec::new();
+	for e in suggestion.iter().flat_map(|s| &s.edit) {
+		let Some(&(offset, len)) = start.get(e.line) else {
+			return Err(invalid(e, "line out of the content"));
+		};
+		let line = &content[offset..offset + len];
+		if e.range.start > e.range.end || e.range.end > line.len() {
+			return Err(invalid(e, "range out of its line"));
+		}
+		if !line.is_char_boundary(e.range.start) || !line.is_char_boundary(e.range.end) {
+			return Err(invalid(e, "range inside a character"));
+		}
+		edit.push((offset + e.range.start..offset + e.range.end, e));
+	}
+
+	// stable, so insertions at the same position keep their order (and go before a replacement)
+	edit.sort_by_key(|(r, _)| (r.start, r.end));
+	for w in edit.windows(2) {
+		let ((a, _), (b, e)) = (&w[0], &w[1]);
+		if b.start < a.end {
+			return Err(invalid(e, "overlapping another edit"));
+		}
+	}
+
+	let mut out = String::with_capacity(content.len());
+	let mut last = 0;
+	for (r, e) in edit {
+		out.push_str(&content[last..r.start]);
+		out.push_str(&e.text);
+		last = r.end;
+	}
+	out.push_str(&content[last..]);
+
+	Ok(out)
+}
+
+/// `(byte offset, length without line ending)` of every line of `content`
+fn line_start(content: &str) -> Vec<(usize, usize)> {
+	let mut offset = 0;
+	content
+		.split_inclusive('\n')
+		.map(|l| {
+			let line = l.strip_suffix('\n').unwrap_or(l);
+			let line = line.strip_suffix('\r').unwrap_or(line);
+			let start = offset;
+			offset += l.len();
+			(start, line.len())
+		})
+		.collect()
+}
+
+fn invalid(e: &Edit, why: &str) -> GenericError {
+	GenericError {
+		error: format!(
+			"cannot apply suggestion at line {line} ({start}..{end}): {why}",
+			line = e.line,
+			start = e.range.start,
+			end = e.range.end,
+		),
+		help: None,
+		source_file: Some(file!().to_string()),
+		source: None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_apply() {
+		let content = "name Jhon\r\nage 42\nclé x";
+
+		let fixed = apply(
+			content,
+			&[
+				Edit::replace(0, 5..9, "John").into(),
+				Suggestion {
+					message: None,
+					edit: vec![Edit::insert(1, 6, " years"), Edit::insert(1, 6, " old")],
+				},
+				Edit::delete(2, 4..6).into(),
+			],
+		)
+		.unwrap();
+		assert_eq!(fixed, "name John\r\nage 42 years old\nclé");
+	}
+
+	#[test]
+	fn test_apply_invalid() {
+		let content = "name John\nclé x";
+
+		assert!(apply(content, &[Edit::insert(2, 0, "x").into()]).is_err());
+		assert!(apply(content, &[Edit::delete(0, 5..42).into()]).is_err());
+		assert!(apply(content, &[Edit::delete(1, 0..3).into()]).is_err());
+		assert!(
+			apply(
+				content,
+				&[
+					Edit::replace(0, 0..4, "Name").into(),
+					Edit::delete(0, 2..6).into()
+				]
+			)
+			.is_err()
+		);
+		// an insertion right before a replacement is fine
+		assert_eq!(
+			apply(
+				content,
+				&[
+					Edit::replace(0, 5..9, "Will").into(),
+					Edit::insert(0, 5, "Sir ").into()
+				]
+			)
+			.unwrap(),
+			"name Sir Will\nclé x"
+		);
+	}
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "derive")]
+
+use hmerr::Context;
+use hmerr::display::Plain;
+use hmerr::parse::Line;
+use hmerr::{Diagnostic, Emitter, GenericError, HmError, ParseFileError, Report};
+
+use std::error::Error;
+
+#[derive(Debug, HmError)]
+#[into]
+enum ConfigError {
+	#[error("missing key `{key}`")]
+	#[help("add the key to the config")]
+	#[code("CONF0001")]
+	Missing {
+		key: String,
+		#[file]
+		file: String,
+		#[line]
+		line: Option<Line>,
+	},
+	#[error("could not read {0}")]
+	Read(String, #[source] std::io::Error),
+	#[error("invalid value")]
+	Invalid {
+		#[help]
+		hint: Option<String>,
+		#[source]
+		source: Option<Box<dyn Error + Send + Sync>>,
+	},
+}
+
+#[derive(Debug, HmError)]
+#[error("{count} {} found", if *count > 1 { "errors" } else { "error" })]
+struct Count {
+	count: usize,
+}
+
+/// no conversion without `#[into]`, its source is not `Send + Sync`
+#[derive(Debug, HmError)]
+#[error("could not load {name}")]
+struct Load {
+	name: String,
+	#[source]
+	cause: Box<dyn Error>,
+}
+
+fn missing() -> ConfigError {
+	ConfigError::Missing {
+		key: "name".to_string(),
+		file: "app.conf".to_string(),
+		line: Some(Line::new("age 42", Some(3), vec![])),
+	}
+}
+
+#[test]
+fn test_derive_message() {
+	assert_eq!(missing().message(), "missing key `name`");
+	assert_eq!(missing().help(), Some("add the key to the config"));
+	assert_eq!(Count { count: 2 }.message(), "2 errors found");
+
+	let e = ConfigError::Read("app.conf".to_string(), std::io::ErrorKind::NotFound.into());
+	assert_eq!(e.message(), "could not read app.conf");
+	assert!(e.source().unwrap().is::<std::io::Error>());
+	assert_eq!(e.help(), None);
+
+	let e = ConfigError::Invalid {
+		hint: Some("use a number".to_string()),
+		source: None,
+	};
+	assert_eq!(e.help(), Some("use a number"));
+	assert!(e.source().is_none());
+}
+
+#[test]
+fn test_derive_display() {
+	let e = missing();
+	let location = e.location().unwrap();
+	assert_eq!(location.file, Some("app.conf"));
+	assert_eq!(location.line.unwrap().index, Some(3));
+
+	let s = Plain(&e).to_string();
+	assert!(s.starts_with("error[CONF0001]:\tmissing key `name`\n"));
+	assert!(s.contains("app.conf:3"));
+	assert!(s.contains("help: add the key to the config"));
+
+	// same layout as the hmerr error it converts into
+	assert_eq!(s, Plain(ParseFileError::from(missing())).to_string());
+}
+
+#[test]
+fn test_derive_into() {
+	let e = GenericError::from(ConfigError::Read(
+		"app.conf".to_string(),
+		std::io::ErrorKind::NotFound.into(),
+	));
+	assert_eq!(e.error, "could not read app.conf");
+	assert!(e.source.unwrap().is::<std::io::Error>());
+
+	let e = ParseFileError::from(missing());
+	assert_eq!(e.file.as_deref(), Some("app.conf"));
+	assert_eq!(e.line.unwrap().index, Some(3));
+	assert_eq!(e.help.as_deref(), Some("add the key to the config"));
+	assert_eq!(e.code.as_deref(), Some("CONF0001"));
+}
+
+#[test]
+fn test_derive_local_source() {
+	let e = Load {
+		name: "app.conf".to_string(),
+		cause: "no such file".into(),
+	};
+	assert_eq!(e.source().unwrap().to_string(), "no such file");
+	let s = Plain(&e).to_string();
+	assert!(s.starts_with("error:\tcould not load app.conf\n"));
+	assert!(s.contains("no such file"));
+}
+
+#[test]
+fn test_derive_report() {
+	// rendered once, with the help replaced and the code kept
+	let e = Err::<(), _>(missing()).help("add it").unwrap_err();
+	let s = Plain(&e).to_string();
+	assert!(s.starts_with("error[CONF0001]:\tmissing key `name`\n"));
+	assert_eq!(s.matches("error").count(), 1);
+	assert!(s.contains("app.conf:3"));
+	assert!(s.contains("help: add it"));
+	assert!(e.downcast_ref::<ConfigError>().is_some());
+
+	let e = Report::from_diagnostic(missing()).with_file("other.conf");
+	let s = Plain(&e).to_string();
+	assert!(s.starts_with("error[CONF0001]:\tmissing key `name`\n"));
+	assert!(s.contains("other.conf:3"));
+	assert!(s.contains("help: add the key to the config"));
+	assert!(e.downcast::<ConfigError>().is_ok());
+
+	let s = Plain(Report::new(missing())).to_string();
+	assert_eq!(s, Plain(missing()).to_string());
+}
+
+#[test]
+fn test_derive_emit() {
+	let mut emitter = Emitter::with_writer(Vec::new());
+	emitter.emit_error(&missing()).unwrap();
+	let out = hmerr::display::strip(&String::from_utf8(emitter.into_writer()).unwrap());
+	assert_eq!(out, Plain(missing()).to_string() + "\n");
+	assert!(out.starts_with("error[CONF0001]:\tmissing key `name`\n"));
+}
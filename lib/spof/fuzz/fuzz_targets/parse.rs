@@ -0,0 +1,174 @@
+//! parse arbitrary files against arbitrary rules
+//!
+//! ```sh
+//! cd lib/spof
+//! cargo +nightly fuzz run parse
+//! ```
+#![no_main]
+
+use arbitrary::Arbitrary;
+use hmerr::ParseFileError;
+use libfuzzer_sys::fuzz_target;
+use spof::{
+	ExpectedLine, ExpectedSize, FileData, FileDataKey, Format, FoundLine, KeyData, Keyword,
+	Occurrence, Order, SpofedFile,
+};
+
+use std::cell::RefCell;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Arbitrary)]
+struct RuleSpec {
+	keyword: String,
+	format: String,
+	size: Size,
+	occurrence: Occ,
+	order: Option<Ord>,
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+enum Size {
+	Fixed,
+	Undefined,
+	Range(u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+enum Occ {
+	Once,
+	Optional,
+	ZeroOrMore,
+	OneOrMore,
+	Exactly(u8),
+	Range(u8, u8),
+}
+
+#[derive(Debug, Clone, Arbitrary)]
+enum Ord {
+	First,
+	Last,
+	Before(String),
+	After(String),
+	BeforeLine(u8),
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+	rule: Vec<RuleSpec>,
+	comment: Option<String>,
+	content: String,
+}
+
+thread_local! {
+	static RULE: RefCell<Vec<RuleSpec>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Key(usize);
+
+impl From<Key> for usize {
+	fn from(k: Key) -> usize {
+		k.0
+	}
+}
+
+impl FromStr for Key {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		RULE.with(|r| {
+			r.borrow()
+				.iter()
+				.position(|spec| spec.keyword == s)
+				.map(Key)
+				.ok_or(())
+		})
+	}
+}
+
+impl FileDataKey for Key {
+	fn build() -> FileData<Self> {
+		RULE.with(|r| {
+			FileData::new(
+				r.borrow()
+					.iter()
+					.map(|spec| {
+						let size = match spec.size {
+							Size::Fixed => ExpectedSize::Fixed,
+							Size::Undefined => ExpectedSize::Undefined,
+							Size::Range(a, b) => ExpectedSize::Range(a.into(), b.into()),
+						};
+						let occurrence = match spec.occurrence {
+							Occ::Once => Occurrence::Once,
+							Occ::Optional => Occurrence::Optional,
+							Occ::ZeroOrMore => Occurrence::ZeroOrMore,
+							Occ::OneOrMore => Occurrence::OneOrMore,
+							Occ::Exactly(n) => Occurrence::Exactly(n.into()),
+							Occ::Range(a, b) => Occurrence::Range(a.into(), b.into()),
+						};
+						let el = ExpectedLine::new(
+							Keyword::new(spec.keyword.as_str(), "fuzzed"),
+							Format::new(spec.format.as_str(), size),
+							occurrence,
+						);
+						let el = match &spec.order {
+							None => el,
+							Some(Ord::First) => el.with_order(Order::First),
+							Some(Ord::Last) => el.with_order(Order::Last),
+							Some(Ord::Before(k)) => el.with_order(Order::before(k.as_str())),
+							Some(Ord::After(k)) => el.with_order(Order::after(k.as_str())),
+							Some(Ord::BeforeLine(n)) => {
+								el.with_order(Order::BeforeLine((*n).into()))
+							}
+						};
+						KeyData::new(FoundLine::new(), el)
+					})
+					.collect(),
+			)
+		})
+	}
+}
+
+fn parse(content: &str, comment: Option<&str>) -> hmerr::Result<SpofedFile<Key>> {
+	SpofedFile::from_reader("fuzzed", content.as_bytes(), comment, Key::build())
+}
+
+fuzz_target!(|input: Input| {
+	RULE.with(|r| *r.borrow_mut() = input.rule.clone());
+	// a comment marker with whitespace could be formed again by the writer joining tokens
+	let comment = input
+		.comment
+		.as_deref()
+		.filter(|c| !c.chars().any(char::is_whitespace));
+	let line_count = input.content.lines().count();
+
+	let e = match parse(&input.content, comment) {
+		Ok(f) => {
+			// round trip through the writer must be stable
+			let mut written = Vec::new();
+			f.write(&mut written).unwrap();
+			let written = String::from_utf8(written).unwrap();
+			// the comment marker can not appear in a token, so the written file has none
+			let again = parse(&written, comment).expect("written file should parse");
+			let mut rewritten = Vec::new();
+			again.write(&mut rewritten).unwrap();
+			assert_eq!(written.as_bytes(), rewritten.as_slice());
+
+			match f.check_occurrence() {
+				Ok(_) => return,
+				Err(e) => e,
+			}
+		}
+		Err(e) => e,
+	};
+
+	let mut e: Option<&(dyn std::error::Error + 'static)> = Some(e.as_ref());
+	while let Some(err) = e {
+		if let Some(pfe) = err.downcast_ref::<ParseFileError>()
+			&& let Some(index) = pfe.line.as_ref().and_then(|l| l.index)
+		{
+			assert!(index < line_count, "line {index} out of {line_count} lines");
+		}
+		e = err.source();
+	}
+});
@@ -0,0 +1,24 @@
+//! language server for a small `.obj` like format, to try the `lsp` feature from an editor
+//!
+//! ```sh
+//! cargo install --path lib/spof --features lsp
+//! ```
+//!
+//! then point the editor to `spof-lsp` for the `*.spof` files
+
+use spof::lsp::Server;
+use spof::rule;
+
+rule!(
+	enum ObjRule {
+		Name => "o", "NAME", Fixed, Optional, "the name of the object",
+		Vertex => "v", "X Y Z [W]", (3, 4), OneOrMore, "a vertex";
+			spof::Order::before("f"),
+		Face => "f", "V1 V2 V3", Fixed, ZeroOrMore, "a triangle, from the index of 3 vertices",
+		Shading => "s", "on|off", Fixed, Optional, "smooth shading",
+	}
+);
+
+fn main() -> hmerr::Result<()> {
+	Server::<ObjRule>::new(Some("#")).serve()
+}
@@ -0,0 +1,103 @@
+//! stable codes of the errors of spof, shown in their header (`error[SPOF0002]:`)
+//!
+//! `register` adds their explanation to `hmerr::explain`
+
+use hmerr::explain::{self, Explanation};
+
+/// the file is not valid in its `Encoding`
+pub const ENCODING: &str = "SPOF0001";
+/// a line starts with a keyword that has no rule
+pub const UNSUPPORTED_KEYWORD: &str = "SPOF0002";
+/// a line has fewer tokens than its `Format`
+pub const MISSING_TOKEN: &str = "SPOF0003";
+/// a line does not have as many tokens as its `Format`
+pub const TOKEN_COUNT: &str = "SPOF0004";
+/// a token does not satisfy its `Constraint`
+pub const INVALID_VALUE: &str = "SPOF0005";
+/// a keyword does not appear as many times as its `Occurrence`
+pub const OCCURRENCE: &str = "SPOF0006";
+/// a keyword is not placed as its `Order`
+pub const ORDER: &str = "SPOF0007";
+/// a token could not be parsed into the requested type
+pub const PARSE: &str = "SPOF0008";
+
+/// register the explanation of every spof code in `hmerr::explain`
+pub fn register() {
+	for e in explanation() {
+		explain::register(e);
+	}
+}
+
+fn explanation() -> [Explanation; 8] {
+	[
+		Explanation::new(
+			ENCODING,
+			"invalid encoding",
+			"the file contains a byte sequence that is not valid in the `Encoding` it is read with.\n\
+			 `Encoding::Auto` reads UTF-8 (with or without BOM) and UTF-16 with a BOM.",
+		)
+		.right("save the file as UTF-8, or read it with `Encoding::Latin1`"),
+		Explanation::new(
+			UNSUPPORTED_KEYWORD,
+			"unsupported keyword",
+			"every line starts with a keyword, and every keyword must have a rule.",
+		)
+		.wrong("tv 1.0 2.0")
+		.right("vt 1.0 2.0"),
+		Explanation::new(
+			MISSING_TOKEN,
+			"missing token",
+			"a line has fewer tokens than the `Format` of its keyword.",
+		)
+		.wrong("v 1.0 2.0")
+		.right("v 1.0 2.0 3.0"),
+		Explanation::new(
+			TOKEN_COUNT,
+			"wrong number of tokens",
+			"a line does not have as many tokens as the `Format` of its keyword.",
+		)
+		.wrong("v 1.0 2.0 3.0 4.0")
+		.right("v 1.0 2.0 3.0"),
+		Explanation::new(
+			INVALID_VALUE,
+			"invalid value",
+			"a token does not satisfy the `Constraint` of its position in the `Format`.",
+		)
+		.wrong("age -42")
+		.right("age 42"),
+		Explanation::new(
+			OCCURRENCE,
+			"wrong number of occurrences",
+			"a keyword appears fewer or more times than its `Occurrence` allows.",
+		)
+		.wrong("name John\nname Jane")
+		.right("name John"),
+		Explanation::new(
+			ORDER,
+			"misplaced keyword",
+			"a keyword is not placed as its `Order` requires, relative to the file or to another keyword.",
+		)
+		.wrong("end\nbegin")
+		.right("begin\nend"),
+		Explanation::new(
+			PARSE,
+			"could not parse token",
+			"a token could not be parsed into the type requested from the `SpofedFile`.",
+		)
+		.wrong("v 1.0 two 3.0")
+		.right("v 1.0 2.0 3.0"),
+	]
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_register() {
+		register();
+		for e in explanation() {
+			assert_eq!(explain::get(&e.code).map(|e| e.title), Some(e.title));
+		}
+	}
+}
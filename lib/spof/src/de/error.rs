@@ -0,0 +1,67 @@
+use ansi::abbrev::{B, D, Y};
+use hmerr::ParseFileError;
+use hmerr::parse::Line;
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub struct Error {
+	error: String,
+	line: Option<Line>,
+	help: Option<String>,
+}
+
+impl Error {
+	pub fn new(error: impl Into<String>, line: Option<Line>) -> Self {
+		Self {
+			error: error.into(),
+			line,
+			help: None,
+		}
+	}
+
+	/// attach `line` if the error does not point to a line yet
+	pub fn at(mut self, line: impl FnOnce() -> Line) -> Self {
+		if self.line.is_none() {
+			self.line = Some(line());
+		}
+		self
+	}
+
+	/// attach `help` if the error does not have one yet
+	pub fn with_help(mut self, help: impl FnOnce() -> String) -> Self {
+		if self.help.is_none() {
+			self.help = Some(help());
+		}
+		self
+	}
+
+	pub fn into_parse_file_error(self, file: String) -> ParseFileError {
+		ParseFileError::new(
+			self.error,
+			file,
+			self.line,
+			self.help,
+			Some(file!().to_string()),
+			None,
+		)
+	}
+}
+
+impl serde::de::Error for Error {
+	fn custom<T: Display>(msg: T) -> Self {
+		Self::new(msg.to_string(), None)
+	}
+
+	fn missing_field(field: &'static str) -> Self {
+		Self::new(format!("missing keyword {B}{Y}{field}{D}"), None)
+	}
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.error)
+	}
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,381 @@
+use super::Error;
+
+use crate::{KeyData, ParsedLine};
+
+use ansi::abbrev::{B, D, G, R, Y};
+use hmerr::parse::{Line, Wrong};
+use serde::de::value::SeqDeserializer;
+use serde::de::{Deserializer, IntoDeserializer, Visitor};
+
+use std::str::FromStr;
+
+/// forward `$method` to the deserializer returned by `self.$to()?`
+/// and attach `self.$line()` to any error that does not point to a line yet
+macro_rules! forward {
+	($to:ident, $line:ident: $($method:ident($($arg:ident: $ty:ty),*))*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Error> {
+				self.$to()?
+					.$method($($arg,)* visitor)
+					.map_err(|e| e.at(|| self.$line()))
+			}
+		)*
+	};
+}
+
+/// parse the token with `FromStr` and hand it to `$visit`
+macro_rules! parse {
+	($($method:ident => $visit:ident),* $(,)?) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+				visitor
+					.$visit(self.parse()?)
+					.map_err(|e: Error| e.at(|| self.line()))
+			}
+		)*
+	};
+}
+
+/// all the lines of a keyword
+///
+/// a repeatable keyword (see `Occurrence::is_repeatable`) is a sequence of lines,
+/// any other keyword is deserialized from its single line
+#[derive(Clone, Copy)]
+pub struct FoundLineDeserializer<'de> {
+	kd: &'de KeyData,
+}
+
+impl<'de> FoundLineDeserializer<'de> {
+	pub fn new(kd: &'de KeyData) -> Self {
+		Self { kd }
+	}
+
+	fn once(&self) -> Result<ParsedLineDeserializer<'de>, Error> {
+		self.kd
+			.data
+			.once()
+			.map(ParsedLineDeserializer::new)
+			.ok_or_else(|| {
+				Error::new(
+					format!(
+						"missing keyword {B}{Y}{keyword}{D}",
+						keyword = self.kd.rule.k.keyword
+					),
+					None,
+				)
+			})
+	}
+
+	fn lines<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let mut seq = SeqDeserializer::new(self.kd.data.0.iter().map(ParsedLineDeserializer::new));
+		let value = visitor.visit_seq(&mut seq)?;
+		seq.end()?;
+		Ok(value)
+	}
+
+	fn first_line(&self) -> Line {
+		self.kd
+			.data
+			.once()
+			.cloned()
+			.map(Line::from)
+			.unwrap_or_default()
+	}
+}
+
+impl<'de> Deserializer<'de> for FoundLineDeserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		if self.kd.rule.occurrence.is_repeatable() {
+			self.lines(visitor)
+		} else {
+			self.once()?.deserialize_any(visitor)
+		}
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		if self.kd.rule.occurrence.is_repeatable() {
+			self.lines(visitor)
+		} else {
+			self.once()?.deserialize_seq(visitor)
+		}
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		if self.kd.data.is_empty() {
+			visitor.visit_none()
+		} else {
+			visitor.visit_some(self)
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+
+	forward! { once, first_line:
+		deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+		deserialize_i128() deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+		deserialize_u128() deserialize_f32() deserialize_f64() deserialize_char()
+		deserialize_str() deserialize_string() deserialize_bytes() deserialize_byte_buf()
+		deserialize_unit() deserialize_unit_struct(name: &'static str)
+		deserialize_tuple(len: usize) deserialize_tuple_struct(name: &'static str, len: usize)
+		deserialize_map() deserialize_struct(name: &'static str, fields: &'static [&'static str])
+		deserialize_enum(name: &'static str, variants: &'static [&'static str])
+		deserialize_identifier()
+	}
+}
+
+/// the tokens of a single line
+#[derive(Clone, Copy)]
+pub struct ParsedLineDeserializer<'de> {
+	pl: &'de ParsedLine,
+}
+
+impl<'de> ParsedLineDeserializer<'de> {
+	pub fn new(pl: &'de ParsedLine) -> Self {
+		Self { pl }
+	}
+
+	fn single(&self) -> Result<TokenDeserializer<'de>, Error> {
+		match self.pl.0.len() {
+			1 => Ok(TokenDeserializer::new(self.pl, 0)),
+			n => Err(Error::new(
+				format!("expected {B}{G}1{D} token, got {B}{R}{n}{D}"),
+				Some(self.line()),
+			)),
+		}
+	}
+
+	fn tokens<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let mut seq =
+			SeqDeserializer::new((0..self.pl.0.len()).map(|i| TokenDeserializer::new(self.pl, i)));
+		visitor
+			.visit_seq(&mut seq)
+			.and_then(|value| seq.end().map(|_| value))
+			.map_err(|e| e.at(|| self.line()))
+	}
+
+	fn line(&self) -> Line {
+		self.pl.clone().into()
+	}
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ParsedLineDeserializer<'de> {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self {
+		self
+	}
+}
+
+impl<'de> Deserializer<'de> for ParsedLineDeserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		if self.pl.0.len() == 1 {
+			self.single()?.deserialize_any(visitor)
+		} else {
+			self.tokens(visitor)
+		}
+	}
+
+	fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		if self.pl.0.len() == 1 {
+			self.single()?.deserialize_str(visitor)
+		} else {
+			visitor
+				.visit_string(self.pl.0.join(" "))
+				.map_err(|e: Error| e.at(|| self.line()))
+		}
+	}
+
+	fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_str(visitor)
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.tokens(visitor)
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(
+		self,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.tokens(visitor)
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.tokens(visitor)
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.tokens(visitor)
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.pl.0.len() {
+			0 => visitor.visit_unit(),
+			n => Err(Error::new(
+				format!("expected {B}{G}0{D} token, got {B}{R}{n}{D}"),
+				Some(self.line()),
+			)),
+		}
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_unit(visitor)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+
+	forward! { single, line:
+		deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+		deserialize_i128() deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+		deserialize_u128() deserialize_f32() deserialize_f64() deserialize_char()
+		deserialize_bytes() deserialize_byte_buf() deserialize_map()
+		deserialize_enum(name: &'static str, variants: &'static [&'static str])
+		deserialize_identifier()
+	}
+}
+
+/// a single token of a line
+#[derive(Clone, Copy)]
+pub struct TokenDeserializer<'de> {
+	pl: &'de ParsedLine,
+	index: usize,
+}
+
+impl<'de> TokenDeserializer<'de> {
+	fn new(pl: &'de ParsedLine, index: usize) -> Self {
+		Self { pl, index }
+	}
+
+	fn token(&self) -> &'de str {
+		&self.pl.0[self.index]
+	}
+
+	fn parse<T: FromStr>(&self) -> Result<T, Error> {
+		self.token().parse::<T>().map_err(|_| {
+			Error::new(
+				format!(
+					"could not parse {B}{R}{token}{D} as {B}{G}{t}{D}",
+					token = self.token(),
+					t = std::any::type_name::<T>(),
+				),
+				Some(self.line()),
+			)
+		})
+	}
+
+	/// the line with only this token highlighted
+	fn line(&self) -> Line {
+		let start = self.pl.0[..self.index].iter().map(|t| t.len() + 1).sum();
+		Line::new(
+			self.pl.0.join(" "),
+			Some(self.pl.1),
+			vec![Wrong::Bit((start, self.token().len()))],
+		)
+	}
+}
+
+impl<'de> IntoDeserializer<'de, Error> for TokenDeserializer<'de> {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self {
+		self
+	}
+}
+
+impl<'de> Deserializer<'de> for TokenDeserializer<'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor
+			.visit_borrowed_str(self.token())
+			.map_err(|e: Error| e.at(|| self.line()))
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor
+			.visit_enum(self.token().into_deserializer())
+			.map_err(|e: Error| e.at(|| self.line()))
+	}
+
+	parse! {
+		deserialize_bool => visit_bool,
+		deserialize_i8 => visit_i8,
+		deserialize_i16 => visit_i16,
+		deserialize_i32 => visit_i32,
+		deserialize_i64 => visit_i64,
+		deserialize_i128 => visit_i128,
+		deserialize_u8 => visit_u8,
+		deserialize_u16 => visit_u16,
+		deserialize_u32 => visit_u32,
+		deserialize_u64 => visit_u64,
+		deserialize_u128 => visit_u128,
+		deserialize_f32 => visit_f32,
+		deserialize_f64 => visit_f64,
+		deserialize_char => visit_char,
+	}
+
+	serde::forward_to_deserialize_any! {
+		str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+		identifier ignored_any
+	}
+}
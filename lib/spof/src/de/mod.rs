@@ -0,0 +1,248 @@
+mod error;
+use error::Error;
+mod line;
+use line::FoundLineDeserializer;
+
+use crate::{FileData, FileDataKey, KeyData, SpofedFile};
+
+use hmerr::ParseFileError;
+use serde::Deserialize;
+use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, Visitor};
+
+impl<K: FileDataKey> SpofedFile<K> {
+	/// deserialize the file into any `T` implementing `serde::Deserialize`
+	///
+	/// each keyword of the rule is a field of `T`:
+	/// * a keyword that can occur more than once is a sequence of lines (`Vec<_>`)
+	/// * an `Optional` keyword can be an `Option<_>`
+	/// * a line with several tokens is a sequence, a tuple or a struct
+	/// * a line with a single token is any `FromStr` primitive or a unit enum variant
+	/// * a line deserialized as a `String` is all of its tokens joined with a space
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// use serde::Deserialize;
+	/// use spof::{FileDataKey, SpofedFile, rule};
+	///
+	/// rule!(
+	///     enum ObjRule {
+	///         Name => "name", "string", Undefined, Optional, "the name of the object",
+	///         Color => "color", "R G B", Fixed, Once, "the color of the object",
+	///         Vertex => "v", "X Y Z", Fixed, OneOrMore, "a vertex of the object",
+	///     }
+	/// );
+	///
+	/// #[derive(Deserialize)]
+	/// struct Color {
+	///     r: u8,
+	///     g: u8,
+	///     b: u8,
+	/// }
+	///
+	/// #[derive(Deserialize)]
+	/// struct Obj {
+	///     name: Option<String>,
+	///     color: Color,
+	///     v: Vec<(f32, f32, f32)>,
+	/// }
+	///
+	/// let f = SpofedFile::new("object.obj", Some("#"), ObjRule::build()).unwrap();
+	/// let obj: Obj = f.deserialize().unwrap();
+	/// ```
+	pub fn deserialize<'de, T>(&'de self) -> Result<T, Box<ParseFileError>>
+	where
+		T: Deserialize<'de>,
+	{
+		T::deserialize(FileDeserializer { data: self.data() })
+			.map_err(|e| Box::new(e.into_parse_file_error(self.name())))
+	}
+}
+
+struct FileDeserializer<'de, K: FileDataKey> {
+	data: &'de FileData<K>,
+}
+
+impl<'de, K: FileDataKey> Deserializer<'de> for FileDeserializer<'de, K> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_map(KeywordAccess {
+			iter: self.data.iter(),
+			current: None,
+		})
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf option unit unit_struct newtype_struct seq tuple
+		tuple_struct map struct enum identifier ignored_any
+	}
+}
+
+struct KeywordAccess<'de> {
+	iter: std::slice::Iter<'de, KeyData>,
+	current: Option<&'de KeyData>,
+}
+
+impl<'de> MapAccess<'de> for KeywordAccess<'de> {
+	type Error = Error;
+
+	fn next_key_seed<S>(&mut self, seed: S) -> Result<Option<S::Value>, Error>
+	where
+		S: DeserializeSeed<'de>,
+	{
+		let Some(kd) = self.iter.next() else {
+			return Ok(None);
+		};
+		self.current = Some(kd);
+
+		seed.deserialize(IntoDeserializer::<Error>::into_deserializer(
+			kd.rule.k.keyword.as_str(),
+		))
+		.map(Some)
+	}
+
+	fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, Error>
+	where
+		S: DeserializeSeed<'de>,
+	{
+		let kd = self.current.take().ok_or_else(|| {
+			<Error as serde::de::Error>::custom("value requested before its keyword")
+		})?;
+
+		seed.deserialize(FoundLineDeserializer::new(kd))
+			.map_err(|e| e.with_help(|| kd.rule.help()))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.iter.len())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{FileDataKey, SpofedFile, rule};
+
+	use serde::Deserialize;
+
+	use std::path::PathBuf;
+
+	rule!(
+		enum RuleTest {
+			Name => "name", "string", Undefined, Optional, "the name of the object",
+			Color => "color", "R G B", Fixed, Once, "the color of the object",
+			Position => "position", "X Y Z [W]", (3, 4), Once, "the position of the object",
+			Mode => "mode", "MODE", Fixed, Optional, "the mode of the object",
+			Vertex => "v", "X Y Z", Fixed, ZeroOrMore, "a vertex of the object",
+		}
+	);
+
+	#[derive(Debug, Deserialize, PartialEq)]
+	struct Color {
+		r: u8,
+		g: u8,
+		b: u8,
+	}
+
+	#[derive(Debug, Deserialize, PartialEq)]
+	#[serde(rename_all = "lowercase")]
+	enum Mode {
+		Fast,
+		Safe,
+	}
+
+	#[derive(Debug, Deserialize, PartialEq)]
+	struct Object {
+		name: Option<String>,
+		color: Color,
+		position: Vec<f32>,
+		mode: Option<Mode>,
+		v: Vec<(i32, i32, i32)>,
+	}
+
+	fn spofed(name: &str, content: &str) -> SpofedFile<RuleTest> {
+		let path = std::env::temp_dir().join(PathBuf::from(format!("spof_de_{name}.spof")));
+		std::fs::write(&path, content).unwrap();
+		let f = SpofedFile::new(&path, Some("#"), RuleTest::build()).unwrap();
+		std::fs::remove_file(&path).unwrap();
+		f
+	}
+
+	#[test]
+	fn test_deserialize() {
+		let f = spofed(
+			"valid",
+			"name my object
+color 255 0 42 # comment
+position 1 2 3.5
+mode safe
+v 0 0 0
+v 1 -1 1",
+		);
+
+		let o: Object = f.deserialize().unwrap();
+		assert_eq!(
+			o,
+			Object {
+				name: Some(String::from("my object")),
+				color: Color {
+					r: 255,
+					g: 0,
+					b: 42
+				},
+				position: vec![1.0, 2.0, 3.5],
+				mode: Some(Mode::Safe),
+				v: vec![(0, 0, 0), (1, -1, 1)],
+			}
+		);
+	}
+
+	#[test]
+	fn test_deserialize_missing_optional() {
+		let f = spofed("optional", "color 1 2 3\nposition 1 2 3 4");
+
+		let o: Object = f.deserialize().unwrap();
+		assert_eq!(o.name, None);
+		assert_eq!(o.mode, None);
+		assert_eq!(o.position.len(), 4);
+		assert!(o.v.is_empty());
+	}
+
+	#[test]
+	fn test_deserialize_subset() {
+		#[derive(Deserialize)]
+		struct OnlyColor {
+			color: (u8, u8, u8),
+		}
+
+		let f = spofed("subset", "color 1 2 3\nposition 1 2 3");
+
+		let o: OnlyColor = f.deserialize().unwrap();
+		assert_eq!(o.color, (1, 2, 3));
+	}
+
+	#[test]
+	fn test_deserialize_error() {
+		let f = spofed("error", "color 1 256 3\nposition 1 2 3");
+
+		let e = f.deserialize::<Object>().unwrap_err();
+		let line = e.line.expect("error should point to a line");
+		assert_eq!(line.index, Some(0));
+		assert_eq!(line.line, "1 256 3");
+		assert!(e.file.unwrap().ends_with("spof_de_error.spof"));
+		assert!(e.help.is_some());
+
+		let f = spofed("missing", "position 1 2 3");
+		let e = f.deserialize::<Object>().unwrap_err();
+		assert!(e.error.contains("color"));
+	}
+
+	#[test]
+	fn test_deserialize_unknown_variant() {
+		let f = spofed("variant", "color 1 2 3\nposition 1 2 3\n\nmode slow");
+
+		let e = f.deserialize::<Object>().unwrap_err();
+		assert_eq!(e.line.unwrap().index, Some(3));
+	}
+}
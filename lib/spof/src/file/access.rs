@@ -0,0 +1,74 @@
+use super::{FileDataKey, SpofedFile};
+use crate::ParsedLine;
+
+use hmerr::ParseFileError;
+
+/// accessors returning a `ParseFileError` (with the keyword, the file name and the help of the rule)
+/// instead of panicking like `FoundLine::get_once`, `FoundLine::take_first_token`, `ParsedLine::take`, ...
+///
+/// # Example
+///
+/// ```no_run
+/// use spof::{FileDataKey, SpofedFile, rule};
+///
+/// rule!(
+///     enum ObjRule {
+///         Name => "name", "NAME", Fixed, Optional, "the name of the object",
+///         Position => "position", "X Y Z [W]", (3, 4), Once, "the position of the object",
+///     }
+/// );
+///
+/// fn load() -> hmerr::Result<()> {
+///     let mut f = SpofedFile::new("object.obj", Some("#"), ObjRule::build())?;
+///
+///     let x = f.try_first_token(ObjRule::Position)?;
+///     let w = f.try_token(ObjRule::Position, 0, 3)?;
+///     let name = f.try_take_first_token(ObjRule::Name)?;
+///
+///     Ok(())
+/// }
+/// ```
+impl<K: FileDataKey> SpofedFile<K> {
+	/// see `KeyData::try_once`
+	pub fn try_once(&self, k: K) -> Result<&ParsedLine, Box<ParseFileError>> {
+		self[k].try_once(&self.name())
+	}
+
+	/// see `KeyData::try_first_token`
+	pub fn try_first_token(&self, k: K) -> Result<&String, Box<ParseFileError>> {
+		self[k].try_first_token(&self.name())
+	}
+
+	/// see `KeyData::try_token`
+	pub fn try_token(
+		&self,
+		k: K,
+		line: usize,
+		index: usize,
+	) -> Result<&String, Box<ParseFileError>> {
+		self[k].try_token(&self.name(), line, index)
+	}
+
+	/// see `KeyData::try_take_once`
+	pub fn try_take_once(&mut self, k: K) -> Result<ParsedLine, Box<ParseFileError>> {
+		let name = self.name();
+		self[k].try_take_once(&name)
+	}
+
+	/// see `KeyData::try_take_first_token`
+	pub fn try_take_first_token(&mut self, k: K) -> Result<String, Box<ParseFileError>> {
+		let name = self.name();
+		self[k].try_take_first_token(&name)
+	}
+
+	/// see `KeyData::try_take_token`
+	pub fn try_take_token(
+		&mut self,
+		k: K,
+		line: usize,
+		index: usize,
+	) -> Result<String, Box<ParseFileError>> {
+		let name = self.name();
+		self[k].try_take_token(&name, line, index)
+	}
+}
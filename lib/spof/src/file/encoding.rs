@@ -0,0 +1,228 @@
+use crate::code;
+
+use ansi::abbrev::{B, D, R};
+use hmerr::{ParseFileError, Result, ple, pwe};
+
+/// text encoding of a file
+///
+/// a byte order mark (BOM) matching the encoding is always removed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Encoding {
+	#[default]
+	Auto, // UTF-8 or UTF-16 depending on the BOM, strict UTF-8 without BOM
+	Utf8,      // strict, an invalid byte is an error
+	Utf8Lossy, // invalid bytes are replaced by `U+FFFD`
+	Latin1,    // ISO-8859-1, every byte is a valid character
+	Utf16Le,
+	Utf16Be,
+}
+
+const BOM_UTF8: &[u8] = &[0xEF, 0xBB, 0xBF];
+const BOM_UTF16_LE: &[u8] = &[0xFF, 0xFE];
+const BOM_UTF16_BE: &[u8] = &[0xFE, 0xFF];
+
+impl Encoding {
+	/// encoding given by the byte order mark of `bytes`, if any
+	pub fn detect(bytes: &[u8]) -> Option<Self> {
+		if bytes.starts_with(BOM_UTF8) {
+			Some(Self::Utf8)
+		} else if bytes.starts_with(BOM_UTF16_LE) {
+			Some(Self::Utf16Le)
+		} else if bytes.starts_with(BOM_UTF16_BE) {
+			Some(Self::Utf16Be)
+		} else {
+			None
+		}
+	}
+
+	/// decode `bytes`, `name` is only used to name the file in errors
+	///
+	/// an invalid sequence is reported with its line and the position of the bad byte
+	pub fn decode(self, name: &str, bytes: &[u8]) -> Result<String> {
+		let encoding = match self {
+			Self::Auto => Self::detect(bytes).unwrap_or(Self::Utf8),
+			e => e,
+		};
+
+		match encoding {
+			Self::Auto | Self::Utf8 => {
+				let bytes = bytes.strip_prefix(BOM_UTF8).unwrap_or(bytes);
+				match std::str::from_utf8(bytes) {
+					Ok(s) => Ok(s.to_string()),
+					Err(e) => {
+						let valid = e.valid_up_to();
+						invalid(
+							name,
+							"UTF-8",
+							&String::from_utf8_lossy(bytes),
+							std::str::from_utf8(&bytes[..valid]).unwrap_or_default(),
+							format!("byte {B}{R}{:#04X}{D} at offset {valid}", bytes[valid]),
+						)
+					}
+				}
+			}
+			Self::Utf8Lossy => {
+				let bytes = bytes.strip_prefix(BOM_UTF8).unwrap_or(bytes);
+				Ok(String::from_utf8_lossy(bytes).into_owned())
+			}
+			Self::Latin1 => Ok(bytes.iter().map(|b| *b as char).collect()),
+			Self::Utf16Le => decode_utf16(name, bytes, BOM_UTF16_LE, u16::from_le_bytes),
+			Self::Utf16Be => decode_utf16(name, bytes, BOM_UTF16_BE, u16::from_be_bytes),
+		}
+	}
+}
+
+fn decode_utf16(
+	name: &str,
+	bytes: &[u8],
+	bom: &[u8],
+	from_bytes: fn([u8; 2]) -> u16,
+) -> Result<String> {
+	let bytes = bytes.strip_prefix(bom).unwrap_or(bytes);
+	let unit = bytes.chunks_exact(2).map(|c| from_bytes([c[0], c[1]]));
+
+	let mut decoded = String::with_capacity(bytes.len() / 2);
+	let mut error: Option<(usize, usize)> = None; // (decoded length, byte offset)
+	let mut offset = 0;
+	for c in char::decode_utf16(unit) {
+		match c {
+			Ok(c) => {
+				decoded.push(c);
+				offset += c.len_utf16() * 2;
+			}
+			Err(_) => {
+				error.get_or_insert((decoded.len(), offset));
+				decoded.push(char::REPLACEMENT_CHARACTER);
+				offset += 2;
+			}
+		}
+	}
+	if !bytes.len().is_multiple_of(2) {
+		error.get_or_insert((decoded.len(), offset));
+		decoded.push(char::REPLACEMENT_CHARACTER);
+	}
+
+	match error {
+		None => Ok(decoded),
+		Some((valid, offset)) => invalid(
+			name,
+			"UTF-16",
+			&decoded,
+			&decoded[..valid],
+			format!("sequence at offset {offset}"),
+		),
+	}
+}
+
+/// `lossy` is the whole decoded file, `valid` its part before the first invalid sequence
+///
+/// the invalid sequence is the `U+FFFD` right after `valid` in `lossy`
+fn invalid(name: &str, encoding: &str, lossy: &str, valid: &str, what: String) -> Result<String> {
+	let index = valid.matches('\n').count();
+	// in bytes, as `Wrong::Bit`, `lossy` has the same bytes before the sequence
+	let column = valid.rsplit('\n').next().unwrap_or_default().len();
+	let line = lossy.lines().nth(index).unwrap_or_default();
+
+	Err(Box::new(ParseFileError::new(
+		format!("invalid {encoding} {what}"),
+		name.to_string(),
+		Some(ple!(
+			line,
+			i: index,
+			w: pwe!((column, char::REPLACEMENT_CHARACTER.len_utf8()))
+		)),
+		format!(
+			"the file is not valid {B}{encoding}{D}, save it as {encoding} or read it with another `Encoding`"
+		),
+		Some(file!().to_string()),
+		None,
+	)
+	.with_code(code::ENCODING)))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	use hmerr::parse::Wrong;
+
+	fn position(e: Box<dyn std::error::Error>) -> (Option<usize>, Option<(usize, usize)>) {
+		let e = e.downcast::<ParseFileError>().unwrap();
+		let line = e.line.unwrap();
+		let bit = match line.wrong.as_slice() {
+			[Wrong::Bit(bit)] => Some(*bit),
+			_ => None,
+		};
+		(line.index, bit)
+	}
+
+	#[test]
+	fn test_decode_utf8() {
+		let d = |b: &[u8]| Encoding::Auto.decode("f", b).unwrap();
+		assert_eq!(d(b"key value"), "key value");
+		assert_eq!(d(b"\xEF\xBB\xBFkey value"), "key value");
+		assert_eq!(d("clé é".as_bytes()), "clé é");
+
+		let e = Encoding::Utf8
+			.decode("f", b"a\nkey \xFFvalue\n")
+			.unwrap_err();
+		let (index, wrong) = position(e);
+		assert_eq!(index, Some(1));
+		assert_eq!(wrong, Some((4, 3)));
+
+		// bytes, not chars, before the invalid one
+		let e = Encoding::Utf8.decode("f", b"cl\xC3\xA9 \xFF").unwrap_err();
+		let (index, wrong) = position(e);
+		assert_eq!(index, Some(0));
+		assert_eq!(wrong, Some((5, 3)));
+		let line = "clé \u{FFFD}";
+		assert_eq!(&line[5..8], "\u{FFFD}");
+
+		assert_eq!(
+			Encoding::Utf8Lossy.decode("f", b"a\xFF").unwrap(),
+			"a\u{FFFD}"
+		);
+	}
+
+	#[test]
+	fn test_decode_latin1() {
+		assert_eq!(
+			Encoding::Latin1.decode("f", b"cl\xE9 \xFF").unwrap(),
+			"clé ÿ"
+		);
+	}
+
+	#[test]
+	fn test_decode_utf16() {
+		let le: Vec<u8> = "\u{FEFF}key é\n𝄞"
+			.encode_utf16()
+			.flat_map(|u| u.to_le_bytes())
+			.collect();
+		assert_eq!(Encoding::Auto.decode("f", &le).unwrap(), "key é\n𝄞");
+
+		let be: Vec<u8> = "key é"
+			.encode_utf16()
+			.flat_map(|u| u.to_be_bytes())
+			.collect();
+		assert_eq!(Encoding::Utf16Be.decode("f", &be).unwrap(), "key é");
+
+		// unpaired surrogate on the second line
+		let mut bad: Vec<u8> = "a\nbc"
+			.encode_utf16()
+			.flat_map(|u| u.to_le_bytes())
+			.collect();
+		bad.extend(0xD800u16.to_le_bytes());
+		let (index, wrong) = position(Encoding::Utf16Le.decode("f", &bad).unwrap_err());
+		assert_eq!(index, Some(1));
+		assert_eq!(wrong, Some((2, 3)));
+
+		// unpaired surrogate after a multibyte char
+		let mut bad: Vec<u8> = "é".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+		bad.extend(0xDC00u16.to_le_bytes());
+		let (_, wrong) = position(Encoding::Utf16Le.decode("f", &bad).unwrap_err());
+		assert_eq!(wrong, Some((2, 3)));
+
+		let (index, _) = position(Encoding::Utf16Le.decode("f", b"a\0b").unwrap_err());
+		assert_eq!(index, Some(0));
+	}
+}
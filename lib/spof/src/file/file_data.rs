@@ -42,6 +42,14 @@ impl<K: FileDataKey> FileData<K> {
 	pub fn keywords(&self) -> Vec<&Keyword> {
 		self.0.iter().map(|k| &k.rule.k).collect()
 	}
+
+	pub fn iter(&self) -> std::slice::Iter<'_, KeyData> {
+		self.0.iter()
+	}
+
+	pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, KeyData> {
+		self.0.iter_mut()
+	}
 }
 
 impl<K: FileDataKey> Index<K> for FileData<K> {
@@ -83,17 +91,22 @@ impl<K: FileDataKey> IndexMut<K> for SpofedFile<K> {
 /// * `s:tt` - expected format token size
 /// * `o:tt` - expected line occurrence
 /// * `d:expr` - keyword description
+/// * `; order:expr; ...` - ordering constraints of the keyword |optional|
 ///
 /// # Example
 ///
 /// ```
-/// use spof::rule;
+/// use spof::{Order, rule};
 ///
 /// rule!(
 ///     enum RuleTest {
+///         Header => "header", "VERSION", Fixed, Once, "the header of the file"; Order::First,
 ///         Color => "color", "R G B", Fixed, Once, "the color of the object",
 ///         Position => "position", "X Y Z [W]", (3, 4), Once, "the position of the object", // don't need '[', ']' in format to be optional
 ///         Name => "name", "string", Undefined, Optional, "the name of the object",
+///         Mode => "mode", "fast|safe|debug", Fixed, Optional, "the mode of the object", // token must be one of the values
+///         Tint => "tint", "HEX:/#[0-9a-fA-F]{6}/", Fixed, Optional, "the tint of the object", // token must match the pattern
+///         End => "end", "", Fixed, Once, "the end of the file"; Order::Last; Order::after("color"),
 ///     }
 /// );
 ///
@@ -101,10 +114,14 @@ impl<K: FileDataKey> IndexMut<K> for SpofedFile<K> {
 /// let r = RuleTest::build();
 ///
 /// /* valid file:
+///     header 1
 ///     color 255 0 42
 ///     # some comment
 ///     position 1 2 3 4 # another comment
 ///     name my object
+///     mode fast
+///     tint #ff0040
+///     end
 /// */
 ///
 /// /* invalid file:
@@ -112,11 +129,15 @@ impl<K: FileDataKey> IndexMut<K> for SpofedFile<K> {
 ///    position 1 2        # not enough values
 ///    name                # missing value
 ///    color 255 0 42      # defined twice (Once is defined in the rule)
+///    mode slow           # not one of `fast`, `safe` or `debug`
+///    tint red            # does not match the pattern
+///    end
+///    header 1            # not the first keyword of the file
 /// */
 /// ```
 #[macro_export]
 macro_rules! rule {
-	( $p:vis enum $enum_name:ident { $( $key_enum:ident => $k:expr, $f:expr, $s:tt, $o:tt, $d:expr ),* $(,)? } ) => {
+	( $p:vis enum $enum_name:ident { $( $key_enum:ident => $k:expr, $f:expr, $s:tt, $o:tt, $d:expr $(; $($order:expr);+ )? ),* $(,)? } ) => {
 		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 		$p enum $enum_name {
 			$( $key_enum ),*
@@ -149,7 +170,7 @@ macro_rules! rule {
 								$crate::Keyword::new($k, $d),
 								$crate::Format::new($f, $crate::expected_size!($s)),
 								$crate::occurrence!($o),
-							),
+							)$($(.with_order($order))+)?,
 						),
 					)*
 				])
@@ -1,13 +1,290 @@
-use crate::{ExpectedLine, FoundLine};
+use crate::{ExpectedLine, FoundLine, ParsedLine, Row, code};
+
+use ansi::abbrev::{B, D, G, R, Y};
+use hmerr::parse::Line;
+use hmerr::{ParseFileError, ple, pwe};
 
 #[derive(Debug)]
 pub struct KeyData {
 	pub data: FoundLine,
 	pub rule: ExpectedLine,
+	taken: Vec<(usize, Option<usize>)>, // line, and token (`None` for the whole line) taken out
 }
 
 impl KeyData {
 	pub fn new(data: FoundLine, rule: ExpectedLine) -> Self {
-		Self { data, rule }
+		Self {
+			data,
+			rule,
+			taken: Vec::new(),
+		}
+	}
+
+	/// line `i` of the keyword, with its tokens accessible by name
+	pub fn row(&self, i: usize) -> Option<Row<'_>> {
+		self.data.0.get(i).map(|pl| Row::new(pl, &self.rule.format))
+	}
+
+	pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+		self.data.0.iter().map(|pl| Row::new(pl, &self.rule.format))
+	}
+
+	/// same as `FoundLine::get_once`, but return an error instead of panicking
+	pub fn try_once(&self, file: &str) -> Result<&ParsedLine, Box<ParseFileError>> {
+		let pl = self.try_line(file, 0)?;
+		if self.is_taken(0, None) {
+			return Err(self.taken_error(file, 0, None));
+		}
+		Ok(pl)
+	}
+
+	/// same as `FoundLine::get_first_token`, but return an error instead of panicking
+	pub fn try_first_token(&self, file: &str) -> Result<&String, Box<ParseFileError>> {
+		self.try_token(file, 0, 0)
+	}
+
+	/// token `index` of line `line` of the keyword
+	pub fn try_token(
+		&self,
+		file: &str,
+		line: usize,
+		index: usize,
+	) -> Result<&String, Box<ParseFileError>> {
+		let pl = self.try_line(file, line)?;
+		let token = pl
+			.get(index)
+			.ok_or_else(|| self.missing_token(file, pl, index))?;
+		if self.is_taken(line, Some(index)) {
+			return Err(self.taken_error(file, line, Some(index)));
+		}
+		Ok(token)
+	}
+
+	/// same as `FoundLine::take_once`, but return an error instead of panicking
+	///
+	/// only the first line is taken out, accessing it again is an error
+	pub fn try_take_once(&mut self, file: &str) -> Result<ParsedLine, Box<ParseFileError>> {
+		self.try_once(file)?;
+		self.taken.push((0, None));
+		let pl = &mut self.data.0[0];
+		Ok(ParsedLine::new(std::mem::take(&mut pl.0), pl.1))
+	}
+
+	/// same as `FoundLine::take_first_token`, but return an error instead of panicking
+	///
+	/// only the token is taken out, accessing it again is an error
+	pub fn try_take_first_token(&mut self, file: &str) -> Result<String, Box<ParseFileError>> {
+		self.try_take_token(file, 0, 0)
+	}
+
+	/// same as `ParsedLine::take` on line `line` of the keyword, but return an error instead of panicking
+	///
+	/// only the token is taken out, accessing it again is an error
+	pub fn try_take_token(
+		&mut self,
+		file: &str,
+		line: usize,
+		index: usize,
+	) -> Result<String, Box<ParseFileError>> {
+		self.try_token(file, line, index)?;
+		self.taken.push((line, Some(index)));
+		Ok(std::mem::take(&mut self.data.0[line].0[index]))
+	}
+
+	/// whether token `index` of line `line` (or any of its tokens if `None`) was taken out
+	fn is_taken(&self, line: usize, index: Option<usize>) -> bool {
+		self.taken
+			.iter()
+			.any(|&(l, i)| l == line && (i.is_none() || index.is_none() || i == index))
+	}
+
+	fn try_line(&self, file: &str, line: usize) -> Result<&ParsedLine, Box<ParseFileError>> {
+		self.data.0.get(line).ok_or_else(|| {
+			let keyword = &self.rule.k.keyword;
+			let error = if self.data.is_empty() {
+				format!("missing keyword {B}{Y}{keyword}{D}")
+			} else {
+				format!(
+					"expected at least {B}{G}{n}{D} lines of {B}{Y}{keyword}{D}, got {B}{R}{got}{D}",
+					n = line + 1,
+					got = self.data.len(),
+				)
+			};
+
+			Box::new(
+				ParseFileError::new(
+					error,
+					file.to_string(),
+					None,
+					self.rule.help(),
+					Some(file!().to_string()),
+					None,
+				)
+				.with_code(code::OCCURRENCE),
+			)
+		})
+	}
+
+	fn taken_error(&self, file: &str, line: usize, index: Option<usize>) -> Box<ParseFileError> {
+		let keyword = &self.rule.k.keyword;
+		let what = match index {
+			Some(i) => match self.rule.format.name(i) {
+				Some(name) => format!("{B}{R}`{name}`{D} of "),
+				None => format!("token {B}{R}{n}{D} of ", n = i + 1),
+			},
+			None => String::new(),
+		};
+
+		Box::new(ParseFileError::new(
+			format!(
+				"{what}line {B}{G}{n}{D} of {B}{Y}{keyword}{D} was already taken",
+				n = line + 1
+			),
+			file.to_string(),
+			None,
+			None,
+			Some(file!().to_string()),
+			None,
+		))
+	}
+
+	fn missing_token(&self, file: &str, pl: &ParsedLine, index: usize) -> Box<ParseFileError> {
+		let keyword = &self.rule.k.keyword;
+		let line = if pl.0.is_empty() {
+			keyword.clone()
+		} else {
+			format!("{keyword} {token}", token = pl.0.join(" "))
+		};
+		let end = line.len();
+		let line: Line = ple!(line, i: pl.1, w: pwe!((end, 1)));
+
+		let error = match self.rule.format.name(index) {
+			Some(name) => format!("missing {B}{R}`{name}`{D} after {B}{Y}{keyword}{D}"),
+			None => format!(
+				"missing token {B}{R}{n}{D} after {B}{Y}{keyword}{D}",
+				n = index + 1
+			),
+		};
+
+		Box::new(
+			ParseFileError::new(
+				error,
+				file.to_string(),
+				Some(line),
+				self.rule.help(),
+				Some(file!().to_string()),
+				None,
+			)
+			.with_code(code::MISSING_TOKEN),
+		)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{ExpectedSize, Format, Keyword, Occurrence};
+
+	fn key_data(line: &[&[&str]]) -> KeyData {
+		let mut kd = KeyData::new(
+			FoundLine::new(),
+			ExpectedLine::new(
+				Keyword::new("position", "the position"),
+				Format::new("X Y Z [W]", ExpectedSize::Range(3, 4)),
+				Occurrence::ZeroOrMore,
+			),
+		);
+		for (i, l) in line.iter().enumerate() {
+			kd.data.push(ParsedLine::new(
+				l.iter().map(|t| t.to_string()).collect(),
+				i,
+			));
+		}
+		kd
+	}
+
+	#[test]
+	fn test_try_accessor() {
+		let mut kd = key_data(&[&["1", "2", "3"], &["4", "5", "6", "7"]]);
+		assert_eq!(kd.try_once("f").unwrap().0, ["1", "2", "3"]);
+		assert_eq!(kd.try_first_token("f").unwrap(), "1");
+		assert_eq!(kd.try_token("f", 1, 3).unwrap(), "7");
+
+		let e = kd.try_token("f", 0, 3).unwrap_err();
+		assert_eq!(e.file.as_deref(), Some("f"));
+		assert!(e.help.as_ref().unwrap().contains("X Y Z [W]"));
+		let line = e.line.unwrap();
+		assert_eq!(line.line, "position 1 2 3");
+		assert_eq!(line.index, Some(0));
+
+		let e = kd.try_token("f", 2, 0).unwrap_err();
+		assert!(e.line.is_none());
+
+		assert_eq!(kd.try_take_token("f", 1, 3).unwrap(), "7");
+		assert_eq!(kd.try_take_first_token("f").unwrap(), "1");
+		assert_eq!(kd.try_token("f", 0, 1).unwrap(), "2");
+		assert_eq!(kd.data.len(), 2);
+	}
+
+	#[test]
+	fn test_try_take_twice() {
+		let mut kd = key_data(&[&["1", "2", "3"], &["4", "5", "6", "7"]]);
+		assert_eq!(kd.try_take_token("f", 1, 3).unwrap(), "7");
+		let e = kd.try_take_token("f", 1, 3).unwrap_err();
+		assert!(e.error.contains("`W`"));
+		assert!(e.error.contains("already taken"));
+		assert!(kd.try_token("f", 1, 3).is_err());
+		assert_eq!(kd.try_take_token("f", 1, 2).unwrap(), "6");
+
+		assert_eq!(kd.try_take_first_token("f").unwrap(), "1");
+		assert!(kd.try_take_first_token("f").is_err());
+		// the line is not whole anymore
+		assert!(
+			kd.try_once("f")
+				.unwrap_err()
+				.error
+				.contains("already taken")
+		);
+		assert!(kd.try_take_once("f").is_err());
+
+		let mut kd = key_data(&[&["1", "2", "3"]]);
+		assert_eq!(kd.try_take_once("f").unwrap().0, ["1", "2", "3"]);
+		let e = kd.try_take_once("f").unwrap_err();
+		assert!(e.error.contains("already taken"));
+		assert!(!e.error.contains("missing keyword"));
+		assert!(kd.try_first_token("f").is_err());
+		assert!(kd.try_take_token("f", 0, 2).is_err());
+	}
+
+	#[test]
+	fn test_row() {
+		let kd = key_data(&[&["1", "2", "3"], &["4", "5", "6", "7"]]);
+		let row = kd.row(1).unwrap();
+		assert_eq!(row.get("X").unwrap(), "4");
+		assert_eq!(row.get("W").unwrap(), "7");
+		assert_eq!(row.get("V"), None);
+		assert_eq!(kd.row(2).map(|r| r.1), None);
+
+		let w: Vec<Option<&String>> = kd.rows().map(|r| r.get("W")).collect();
+		assert_eq!(w, [None, Some(&String::from("7"))]);
+
+		let e = kd.try_token("f", 0, 3).unwrap_err();
+		assert!(e.error.contains("`W`"));
+	}
+
+	#[test]
+	fn test_try_accessor_empty() {
+		let mut kd = key_data(&[]);
+		assert!(kd.try_once("f").unwrap_err().error.contains("position"));
+		assert!(kd.try_first_token("f").is_err());
+		assert!(kd.try_take_once("f").is_err());
+		assert!(kd.try_take_first_token("f").is_err());
+		assert!(kd.try_take_token("f", 0, 0).is_err());
+
+		let mut kd = key_data(&[&[]]);
+		let e = kd.try_take_first_token("f").unwrap_err();
+		assert_eq!(e.line.unwrap().line, "position");
+		assert!(kd.try_take_once("f").unwrap().0.is_empty());
+		assert!(kd.try_take_once("f").is_err());
 	}
 }
@@ -1,9 +1,21 @@
+mod access;
+mod encoding;
+pub use encoding::Encoding;
 mod file_data;
 pub use file_data::{FileData, FileDataKey};
 mod key_data;
 use hmerr::ParseFileError;
 pub use key_data::KeyData;
 mod new;
+#[cfg(feature = "lsp")]
+pub(crate) use new::parse;
+mod occurrence;
+mod order;
+mod stack;
+pub use stack::SpofedStack;
+mod write;
+
+use crate::code;
 
 use ansi::abbrev::{B, D, R, Y};
 
@@ -31,6 +43,10 @@ impl<K: FileDataKey> SpofedFile<K> {
 		self.path.to_string_lossy().to_string()
 	}
 
+	pub fn data(&self) -> &FileData<K> {
+		&self.data
+	}
+
 	pub fn parse<T>(&self, k: K) -> Result<Vec<Vec<T>>, Box<ParseFileError>>
 	where
 		T: FromStr,
@@ -48,6 +64,7 @@ impl<K: FileDataKey> SpofedFile<K> {
 				Some(file!().to_string()),
 				None,
 			)
+			.with_code(code::PARSE)
 		})?)
 	}
 }
@@ -1,34 +1,77 @@
-use super::{FileData, FileDataKey, SpofedFile};
-use crate::ParsedLine;
+use super::{Encoding, FileData, FileDataKey, SpofedFile};
+use crate::{ParsedLine, code};
 
 use ansi::abbrev::{B, D, G, Y};
-use hmerr::{Result, pfe, ple, pwe};
+use hmerr::suggestion::{Edit, Suggestion};
+use hmerr::{ParseFileError, Result, ioe, pfe, ple, pwe};
 
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
 impl<K: FileDataKey> SpofedFile<K> {
+	/// read the file with `Encoding::Auto`
 	pub fn new(path: impl Into<PathBuf>, comment: Option<&str>, rule: FileData<K>) -> Result<Self> {
+		Self::with_encoding(path, comment, rule, Encoding::Auto)
+	}
+
+	pub fn with_encoding(
+		path: impl Into<PathBuf>,
+		comment: Option<&str>,
+		rule: FileData<K>,
+		encoding: Encoding,
+	) -> Result<Self> {
+		let path: PathBuf = path.into();
+		let reader = BufReader::new(File::open(&path)?);
+
+		Self::from_reader_with_encoding(path, reader, comment, rule, encoding)
+	}
+
+	/// parse the content of `reader` with `Encoding::Auto`, `path` is only used to name the file
+	pub fn from_reader(
+		path: impl Into<PathBuf>,
+		reader: impl BufRead,
+		comment: Option<&str>,
+		rule: FileData<K>,
+	) -> Result<Self> {
+		Self::from_reader_with_encoding(path, reader, comment, rule, Encoding::Auto)
+	}
+
+	/// parse the content of `reader`, `path` is only used to name the file
+	pub fn from_reader_with_encoding(
+		path: impl Into<PathBuf>,
+		mut reader: impl BufRead,
+		comment: Option<&str>,
+		rule: FileData<K>,
+		encoding: Encoding,
+	) -> Result<Self> {
 		let path: PathBuf = path.into();
 		let name = path.to_string_lossy().to_string();
 
-		let reader = BufReader::new(File::open(&path)?);
+		let mut bytes: Vec<u8> = Vec::new();
+		reader
+			.read_to_end(&mut bytes)
+			.map_err(|e| ioe!(name.as_str(), e))?;
+		let content = encoding.decode(&name, &bytes)?;
 
 		let mut data = rule;
+		let mut raw: Vec<String> = Vec::new();
 
-		for (i, line) in reader.lines().enumerate() {
-			let line = line?;
+		for (i, line) in content.lines().enumerate() {
+			let line = line.to_string();
+			raw.push(line.clone());
 			if let Some((key, pl)) = parse(&name, comment, &data, line, i)? {
 				data[key].data.push(pl);
 			}
 		}
 
+		data.check_order(&name, &raw)?;
+
 		Ok(Self { path, data })
 	}
 }
 
-fn parse<K: FileDataKey>(
+pub(crate) fn parse<K: FileDataKey>(
 	name: &str,
 	comment: Option<&str>,
 	rule: &FileData<K>,
@@ -62,8 +105,19 @@ here is a list of valid keyword:
 					.join("\n")
 			),
 			f: name,
-			l: ple!(line, i: i, w: pwe!(keyword.clone())),
-		)?
+			l: ple!(line.clone(), i: i, w: pwe!(keyword.clone())),
+			c: code::UNSUPPORTED_KEYWORD,
+		)
+		.map_err(|e: ParseFileError| match closest(rule, &keyword) {
+			Some(k) => {
+				let start = line.find(&keyword).unwrap_or_default();
+				e.with_suggestion(Suggestion::new(
+					format!("did you mean `{k}`?"),
+					vec![Edit::replace(i, start..start + keyword.len(), k)],
+				))
+			}
+			None => e,
+		})?
 	};
 
 	rule[keyword].rule.check(name, line, &split, i)?;
@@ -71,6 +125,40 @@ here is a list of valid keyword:
 	Ok(Some((keyword, ParsedLine::new(split, i))))
 }
 
+/// valid keyword closest to the misspelled `keyword`, if close enough
+fn closest<'a, K: FileDataKey>(rule: &'a FileData<K>, keyword: &str) -> Option<&'a str> {
+	let max = (keyword.chars().count() / 3).max(2);
+
+	rule.keywords()
+		.into_iter()
+		.map(|k| (distance(&k.keyword, keyword), k.keyword.as_str()))
+		.filter(|(d, _)| *d <= max)
+		.min_by_key(|(d, _)| *d)
+		.map(|(_, k)| k)
+}
+
+/// levenshtein distance, in characters
+fn distance(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut row: Vec<usize> = (0..=b.len()).collect();
+
+	for (i, ca) in a.chars().enumerate() {
+		let mut diagonal = row[0];
+		row[0] = i + 1;
+		for (j, cb) in b.iter().enumerate() {
+			let above = row[j + 1];
+			row[j + 1] = if ca == *cb {
+				diagonal
+			} else {
+				1 + diagonal.min(above).min(row[j])
+			};
+			diagonal = above;
+		}
+	}
+
+	row[b.len()]
+}
+
 /// remove comment and trim whitespace
 fn pre_parse(line: String, comment: Option<&str>) -> String {
 	let mut line = line;
@@ -87,6 +175,71 @@ fn pre_parse(line: String, comment: Option<&str>) -> String {
 #[cfg(test)]
 mod test {
 	use super::*;
+	use crate::rule;
+
+	rule!(
+		enum RuleTest {
+			Name => "name", "NAME", Fixed, Once, "the name",
+		}
+	);
+
+	#[test]
+	fn test_from_reader_encoding() {
+		let f = SpofedFile::from_reader(
+			"f",
+			"\u{FEFF}name clé\r\n".as_bytes(),
+			Some("#"),
+			RuleTest::build(),
+		)
+		.unwrap();
+		assert_eq!(f[RuleTest::Name].data.get_first_token(), "clé");
+
+		let f = SpofedFile::from_reader_with_encoding(
+			"f",
+			&b"# comment\nname cl\xE9"[..],
+			Some("#"),
+			RuleTest::build(),
+			Encoding::Latin1,
+		)
+		.unwrap();
+		assert_eq!(f[RuleTest::Name].data.get_first_token(), "clé");
+
+		let e = SpofedFile::from_reader(
+			"f",
+			&b"# comment\nname cl\xE9"[..],
+			Some("#"),
+			RuleTest::build(),
+		)
+		.unwrap_err()
+		.downcast::<hmerr::ParseFileError>()
+		.unwrap();
+		assert_eq!(e.line.unwrap().index, Some(1));
+	}
+
+	#[test]
+	fn test_misspelled_keyword_suggestion() {
+		assert_eq!(distance("name", "nmae"), 2);
+		assert_eq!(distance("name", "names"), 1);
+		assert_eq!(distance("", "abc"), 3);
+
+		let content = "# comment\n  nmae John";
+		let e = SpofedFile::from_reader("f", content.as_bytes(), Some("#"), RuleTest::build())
+			.unwrap_err()
+			.downcast::<hmerr::ParseFileError>()
+			.unwrap();
+		assert_eq!(e.code.as_deref(), Some(code::UNSUPPORTED_KEYWORD));
+		assert_eq!(e.suggestion.len(), 1);
+		assert_eq!(
+			hmerr::suggestion::apply(content, &e.suggestion).unwrap(),
+			"# comment\n  name John"
+		);
+
+		let e = SpofedFile::from_reader("f", "xyz John".as_bytes(), None, RuleTest::build())
+			.unwrap_err()
+			.downcast::<hmerr::ParseFileError>()
+			.unwrap();
+		assert!(e.suggestion.is_empty());
+	}
 
 	#[test]
 	fn test_pre_parse_single_char_comment() {
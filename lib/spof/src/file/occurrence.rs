@@ -0,0 +1,94 @@
+use super::{FileData, FileDataKey, SpofedFile};
+use crate::code;
+
+use ansi::abbrev::{B, D, Y};
+use hmerr::{ParseFileError, Result, ple, pwe};
+
+impl<K: FileDataKey> FileData<K> {
+	/// check that every keyword occurs as many times as its rule expects (`ExpectedLine::occurrence`)
+	pub fn check_occurrence(&self, name: &str) -> Result<()> {
+		for kd in self.iter() {
+			let keyword = &kd.rule.k.keyword;
+			let Err(e) = kd.rule.occurrence.check(kd.data.len()) else {
+				continue;
+			};
+
+			// point to the first line over the maximum, if any
+			let line = kd
+				.rule
+				.occurrence
+				.max()
+				.and_then(|max| kd.data.0.get(max))
+				.map(|pl| {
+					ple!(
+						format!("{keyword} {token}", token = pl.0.join(" ")),
+						i: pl.1,
+						w: pwe!((0, keyword.len()))
+					)
+				});
+
+			return Err(Box::new(
+				ParseFileError::new(
+					format!("{B}{Y}{keyword}{D} {e}"),
+					name.to_string(),
+					line,
+					kd.rule.help(),
+					Some(file!().to_string()),
+					None,
+				)
+				.with_code(code::OCCURRENCE),
+			));
+		}
+
+		Ok(())
+	}
+}
+
+impl<K: FileDataKey> SpofedFile<K> {
+	/// check that every keyword occurs as many times as its rule expects
+	pub fn check_occurrence(&self) -> Result<()> {
+		self.data.check_occurrence(&self.name())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{FileDataKey, ParsedLine, rule};
+
+	use hmerr::ParseFileError;
+
+	rule!(
+		enum RuleTest {
+			Color => "color", "R G B", Fixed, Once, "the color",
+			Name => "name", "NAME", Fixed, Optional, "the name",
+			Vertex => "v", "X Y Z", Fixed, OneOrMore, "a vertex",
+		}
+	);
+
+	#[test]
+	fn test_check_occurrence() {
+		let mut data = RuleTest::build();
+		data[RuleTest::Color]
+			.data
+			.push(ParsedLine::new(vec!["1".into(), "2".into(), "3".into()], 0));
+		data[RuleTest::Vertex]
+			.data
+			.push(ParsedLine::new(vec!["0".into(), "0".into(), "0".into()], 1));
+		assert!(data.check_occurrence("test").is_ok());
+
+		data[RuleTest::Color]
+			.data
+			.push(ParsedLine::new(vec!["4".into(), "5".into(), "6".into()], 2));
+		let e = data.check_occurrence("test").unwrap_err();
+		let e = e.downcast_ref::<ParseFileError>().unwrap();
+		let line = e.line.as_ref().unwrap();
+		assert_eq!(line.line, "color 4 5 6");
+		assert_eq!(line.index, Some(2));
+
+		let data = RuleTest::build();
+		let e = data.check_occurrence("test").unwrap_err();
+		let e = e.downcast_ref::<ParseFileError>().unwrap();
+		assert!(e.line.is_none());
+		assert!(e.error.contains("color"));
+	}
+}
@@ -0,0 +1,122 @@
+use super::{FileData, FileDataKey};
+use crate::code;
+
+use ansi::abbrev::{B, D, G, Y};
+use hmerr::parse::Line;
+use hmerr::{ParseFileError, Result, ple, pwe};
+
+impl<K: FileDataKey> FileData<K> {
+	/// check the ordering constraints (`ExpectedLine::order`) of every keyword
+	///
+	/// `raw` is every line of the file as it was read, used to display the misplaced and conflicting lines
+	pub fn check_order(&self, name: &str, raw: &[String]) -> Result<()> {
+		let file: Vec<(&str, Vec<usize>)> = self
+			.iter()
+			.map(|kd| {
+				(
+					kd.rule.k.keyword.as_str(),
+					kd.data.0.iter().map(|pl| pl.1).collect(),
+				)
+			})
+			.collect();
+
+		for (kd, (keyword, line)) in self.iter().zip(file.iter()) {
+			for order in &kd.rule.order {
+				let Err((misplaced, conflict)) = order.check(keyword, line, &file) else {
+					continue;
+				};
+
+				let source = conflict.map(|c| {
+					Box::new(ParseFileError::new(
+						format!(
+							"conflicting {B}{Y}{other}{D}",
+							other = raw
+								.get(c)
+								.and_then(|l| l.split_whitespace().next())
+								.unwrap_or_default()
+						),
+						name.to_string(),
+						Some(keyword_line(raw, c)),
+						None,
+						Some(file!().to_string()),
+						None,
+					)) as Box<dyn std::error::Error + Send + Sync>
+				});
+
+				return Err(Box::new(
+					ParseFileError::new(
+						format!("{B}{Y}{keyword}{D} must come {B}{G}{order}{D}"),
+						name.to_string(),
+						Some(keyword_line(raw, misplaced)),
+						kd.rule.help(),
+						Some(file!().to_string()),
+						source,
+					)
+					.with_code(code::ORDER),
+				));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// the raw line at `index` with its keyword highlighted
+fn keyword_line(raw: &[String], index: usize) -> Line {
+	let line = raw.get(index).cloned().unwrap_or_default();
+	let start = line.len() - line.trim_start().len();
+	let len = line[start..]
+		.split_whitespace()
+		.next()
+		.map(str::len)
+		.unwrap_or_default();
+
+	ple!(line, i: index, w: pwe!((start, len)))
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{FileDataKey, Order, ParsedLine, rule};
+
+	use hmerr::ParseFileError;
+
+	rule!(
+		enum RuleTest {
+			Header => "header", "VERSION", Fixed, Once, "the header"; Order::First,
+			Material => "material", "NAME", Fixed, ZeroOrMore, "a material",
+			Face => "face", "MATERIAL", Fixed, ZeroOrMore, "a face"; Order::after("material"),
+		}
+	);
+
+	fn data(raw: &[&str]) -> (crate::FileData<RuleTest>, Vec<String>) {
+		let mut data = RuleTest::build();
+		for (i, line) in raw.iter().enumerate() {
+			let mut split: Vec<String> = line.split_whitespace().map(String::from).collect();
+			let key = split.remove(0).parse::<RuleTest>().unwrap();
+			data[key].data.push(ParsedLine::new(split, i));
+		}
+		(data, raw.iter().map(|l| l.to_string()).collect())
+	}
+
+	#[test]
+	fn test_check_order() {
+		let (d, raw) = data(&["header 1", "material red", "face red", "face red"]);
+		assert!(d.check_order("test", &raw).is_ok());
+
+		let (d, raw) = data(&["material red", "  header 1"]);
+		let e = d.check_order("test", &raw).unwrap_err();
+		let e = e.downcast_ref::<ParseFileError>().unwrap();
+		let line = e.line.as_ref().unwrap();
+		assert_eq!(line.index, Some(1));
+		assert!(matches!(line.wrong[..], [hmerr::parse::Wrong::Bit((2, 6))]));
+
+		let source = e.source.as_ref().unwrap();
+		let source = source.downcast_ref::<ParseFileError>().unwrap();
+		assert_eq!(source.line.as_ref().unwrap().index, Some(0));
+
+		let (d, raw) = data(&["header 1", "face red", "material red"]);
+		let e = d.check_order("test", &raw).unwrap_err();
+		let e = e.downcast_ref::<ParseFileError>().unwrap();
+		assert_eq!(e.line.as_ref().unwrap().index, Some(1));
+	}
+}
@@ -0,0 +1,225 @@
+use super::{FileData, FileDataKey, KeyData, SpofedFile};
+use crate::FoundLine;
+
+use hmerr::Result;
+
+use std::ops::Index;
+use std::path::{Path, PathBuf};
+
+/// several files parsed against the same rule and merged in order
+///
+/// for a keyword with a maximum number of lines (see `Occurrence::max`),
+/// the last file defining it overrides the previous ones,
+/// so the merged lines are as valid as the ones of each file,
+/// for an unbounded keyword, the lines of every file are appended
+///
+/// every line keeps track of the file it comes from (see `SpofedStack::origin`)
+///
+/// # Example
+///
+/// ```no_run
+/// use spof::{SpofedStack, rule};
+///
+/// rule!(
+///     enum ConfRule {
+///         Port => "port", "PORT", Fixed, Once, "the port to listen on",
+///         Allow => "allow", "IP", Fixed, ZeroOrMore, "an allowed ip",
+///     }
+/// );
+///
+/// let conf = SpofedStack::<ConfRule>::new(["defaults.conf", "site.conf", "user.conf"], Some("#")).unwrap();
+///
+/// // `port` of the last file defining it
+/// let port = conf[ConfRule::Port].data.get_first_token();
+/// let (file, line) = conf.origin(ConfRule::Port, 0).unwrap();
+///
+/// // `allow` of every file
+/// for (i, allow) in conf[ConfRule::Allow].data.0.iter().enumerate() {
+///     let (file, line) = conf.origin(ConfRule::Allow, i).unwrap();
+/// }
+/// ```
+pub struct SpofedStack<K: FileDataKey> {
+	pub path: Vec<PathBuf>,
+	data: FileData<K>,
+	origin: Vec<Vec<usize>>, // for each keyword, for each line, the index of the file in `path`
+}
+
+impl<K: FileDataKey> std::fmt::Debug for SpofedStack<K> {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		writeln!(f, "SpofedStack")?;
+		for path in &self.path {
+			writeln!(f, "     ╞═{path}", path = path.to_string_lossy())?;
+		}
+		write!(f, "{data:?}", data = self.data)
+	}
+}
+
+impl<K: FileDataKey> SpofedStack<K> {
+	/// parse every file of `path` with `K::build()`, from the lowest to the highest priority
+	pub fn new<P>(path: impl IntoIterator<Item = P>, comment: Option<&str>) -> Result<Self>
+	where
+		P: Into<PathBuf>,
+	{
+		let mut stack = Self::empty();
+
+		for p in path {
+			stack.push(SpofedFile::new(p, comment, K::build())?);
+		}
+
+		Ok(stack)
+	}
+
+	/// a stack without any file
+	pub fn empty() -> Self {
+		let data = K::build();
+		let origin = data.iter().map(|_| Vec::new()).collect();
+
+		Self {
+			path: Vec::new(),
+			data,
+			origin,
+		}
+	}
+
+	/// merge `file` on top of the stack
+	pub fn push(&mut self, file: SpofedFile<K>) {
+		let index = self.path.len();
+		self.path.push(file.path);
+
+		let mut layer = file.data;
+		for ((kd, layer), origin) in self
+			.data
+			.iter_mut()
+			.zip(layer.iter_mut())
+			.zip(self.origin.iter_mut())
+		{
+			if layer.data.is_empty() {
+				continue;
+			}
+
+			if kd.rule.occurrence.max().is_some() {
+				kd.data = FoundLine::new();
+				origin.clear();
+			}
+
+			origin.extend(std::iter::repeat_n(index, layer.data.len()));
+			kd.data.0.append(&mut layer.data.0);
+		}
+	}
+
+	/// file and line index the `i`th line of keyword `k` comes from
+	pub fn origin(&self, k: K, i: usize) -> Option<(&Path, usize)> {
+		let file = *self.origin.get(k.into())?.get(i)?;
+		let line = self[k].data.0.get(i)?.1;
+
+		Some((self.path[file].as_path(), line))
+	}
+
+	pub fn data(&self) -> &FileData<K> {
+		&self.data
+	}
+}
+
+impl<K: FileDataKey> Index<K> for SpofedStack<K> {
+	type Output = KeyData;
+
+	fn index(&self, index: K) -> &Self::Output {
+		&self.data[index]
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::rule;
+
+	rule!(
+		enum RuleTest {
+			Port => "port", "PORT", Fixed, Once, "the port",
+			Name => "name", "NAME", Fixed, Optional, "the name",
+			Allow => "allow", "IP", Fixed, ZeroOrMore, "an allowed ip",
+		}
+	);
+
+	rule!(
+		enum RuleBounded {
+			Port => "port", "PORT", Fixed, Once, "the port",
+			Dns => "dns", "IP", Fixed, 2, "the two dns servers",
+		}
+	);
+
+	fn write(name: &str, content: &str) -> PathBuf {
+		let path = std::env::temp_dir().join(format!("spof_stack_{name}.conf"));
+		std::fs::write(&path, content).unwrap();
+		path
+	}
+
+	#[test]
+	fn test_stack() {
+		let defaults = write("defaults", "port 80\nname default\nallow 127.0.0.1");
+		let site = write("site", "port 8080\nallow 10.0.0.1\nallow 10.0.0.2");
+		let user = write("user", "# only a comment\nname user");
+
+		let stack = SpofedStack::<RuleTest>::new([&defaults, &site, &user], Some("#")).unwrap();
+		for p in [&defaults, &site, &user] {
+			std::fs::remove_file(p).unwrap();
+		}
+
+		assert_eq!(stack.path.len(), 3);
+
+		assert_eq!(stack[RuleTest::Port].data.get_first_token(), "8080");
+		assert_eq!(stack.origin(RuleTest::Port, 0), Some((site.as_path(), 0)));
+		assert_eq!(stack.origin(RuleTest::Port, 1), None);
+
+		assert_eq!(stack[RuleTest::Name].data.get_first_token(), "user");
+		assert_eq!(stack.origin(RuleTest::Name, 0), Some((user.as_path(), 1)));
+
+		let allow: Vec<&String> = stack[RuleTest::Allow]
+			.data
+			.0
+			.iter()
+			.map(|pl| &pl.0[0])
+			.collect();
+		assert_eq!(allow, ["127.0.0.1", "10.0.0.1", "10.0.0.2"]);
+		assert_eq!(
+			stack.origin(RuleTest::Allow, 0),
+			Some((defaults.as_path(), 2))
+		);
+		assert_eq!(stack.origin(RuleTest::Allow, 2), Some((site.as_path(), 2)));
+	}
+
+	#[test]
+	fn test_stack_bounded() {
+		let a = write("bounded_a", "port 80\ndns 1.1.1.1\ndns 8.8.8.8");
+		let b = write("bounded_b", "port 81\ndns 9.9.9.9\ndns 10.0.0.53");
+
+		let stack = SpofedStack::<RuleBounded>::new([&a, &b], Some("#")).unwrap();
+		for p in [&a, &b] {
+			std::fs::remove_file(p).unwrap();
+		}
+
+		let dns: Vec<&String> = stack[RuleBounded::Dns]
+			.data
+			.0
+			.iter()
+			.map(|pl| &pl.0[0])
+			.collect();
+		assert_eq!(dns, ["9.9.9.9", "10.0.0.53"]);
+		assert_eq!(stack.origin(RuleBounded::Dns, 0), Some((b.as_path(), 1)));
+		assert_eq!(stack.origin(RuleBounded::Dns, 2), None);
+	}
+
+	#[test]
+	fn test_stack_error() {
+		let good = write("good", "port 80");
+		let bad = write("bad", "port");
+
+		let e = SpofedStack::<RuleTest>::new([&good, &bad], Some("#")).unwrap_err();
+		for p in [&good, &bad] {
+			std::fs::remove_file(p).unwrap();
+		}
+
+		let e = e.downcast_ref::<hmerr::ParseFileError>().unwrap();
+		assert_eq!(e.file.as_deref(), Some(bad.to_string_lossy().as_ref()));
+	}
+}
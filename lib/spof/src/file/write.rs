@@ -0,0 +1,70 @@
+use super::{FileData, FileDataKey, SpofedFile};
+
+use std::io::{self, Write};
+
+impl<K: FileDataKey> FileData<K> {
+	/// write every parsed line back, in the order they were found,
+	/// as `keyword token token ...`
+	///
+	/// comments, empty lines and extra whitespace are not kept
+	pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		let mut line: Vec<(usize, &str, &Vec<String>)> = self
+			.iter()
+			.flat_map(|kd| {
+				kd.data
+					.0
+					.iter()
+					.map(|pl| (pl.1, kd.rule.k.keyword.as_str(), &pl.0))
+			})
+			.collect();
+		line.sort_by_key(|(i, _, _)| *i);
+
+		for (_, keyword, token) in line {
+			if token.is_empty() {
+				writeln!(w, "{keyword}")?;
+			} else {
+				writeln!(w, "{keyword} {token}", token = token.join(" "))?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl<K: FileDataKey> SpofedFile<K> {
+	/// see `FileData::write`
+	pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		self.data.write(w)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{FileDataKey, SpofedFile, rule};
+
+	rule!(
+		enum RuleTest {
+			Color => "color", "R G B", Fixed, Once, "the color",
+			End => "end", "", Fixed, Optional, "the end",
+			Vertex => "v", "X Y Z", Fixed, ZeroOrMore, "a vertex",
+		}
+	);
+
+	#[test]
+	fn test_write() {
+		let content = "v 1 2 3 # comment\n\n\tcolor  1 2\t3\nv 4 5 6\n   end";
+		let f = SpofedFile::from_reader("test", content.as_bytes(), Some("#"), RuleTest::build())
+			.unwrap();
+
+		let mut out = Vec::new();
+		f.write(&mut out).unwrap();
+		let out = String::from_utf8(out).unwrap();
+		assert_eq!(out, "v 1 2 3\ncolor 1 2 3\nv 4 5 6\nend\n");
+
+		let f =
+			SpofedFile::from_reader("test", out.as_bytes(), Some("#"), RuleTest::build()).unwrap();
+		let mut again = Vec::new();
+		f.write(&mut again).unwrap();
+		assert_eq!(String::from_utf8(again).unwrap(), out);
+	}
+}
@@ -1,13 +1,27 @@
+pub mod code;
+
 mod file;
-pub use file::{FileData, FileDataKey, KeyData, SpofedFile};
+pub use file::{Encoding, FileData, FileDataKey, KeyData, SpofedFile, SpofedStack};
 
 mod rule;
 pub use rule::{
+	constraint::Constraint,
 	expected_line::ExpectedLine,
 	format::{ExpectedSize, Format},
 	keyword::Keyword,
 	occurrence::Occurrence,
+	order::Order,
+	pattern::Pattern,
 };
 
 mod line;
-pub use line::{FoundLine, ParsedLine};
+pub use line::{FoundLine, ParsedLine, Row};
+
+mod watch;
+pub use watch::Watcher;
+
+#[cfg(feature = "serde")]
+mod de;
+
+#[cfg(feature = "lsp")]
+pub mod lsp;
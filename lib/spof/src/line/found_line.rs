@@ -32,10 +32,16 @@ impl FoundLine {
 		self.0.first().and_then(|pl| pl.get(0))
 	}
 
+	/// # Panics
+	///
+	/// if there is no line, see `KeyData::try_once` for a fallible version
 	pub fn get_once(&self) -> &ParsedLine {
 		self.0.first().expect("FoundLine is empty")
 	}
 
+	/// # Panics
+	///
+	/// if there is no line (or no token), see `KeyData::try_first_token` for a fallible version
 	pub fn get_first_token(&self) -> &String {
 		self.0
 			.first()
@@ -44,10 +50,16 @@ impl FoundLine {
 			.expect("FoundLine is empty")
 	}
 
+	/// # Panics
+	///
+	/// if there is no line, see `KeyData::try_take_once` for a fallible version
 	pub fn take_once(self) -> ParsedLine {
 		self.0.into_iter().next().expect("FoundLine is empty")
 	}
 
+	/// # Panics
+	///
+	/// if there is no line (or no token), see `KeyData::try_take_first_token` for a fallible version
 	pub fn take_first_token(self) -> String {
 		self.0
 			.into_iter()
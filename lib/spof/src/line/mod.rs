@@ -2,3 +2,5 @@ mod found_line;
 pub use found_line::FoundLine;
 mod parsed_line;
 pub use parsed_line::ParsedLine;
+mod row;
+pub use row::Row;
@@ -20,6 +20,9 @@ impl ParsedLine {
 		self.0.get(index)
 	}
 
+	/// # Panics
+	///
+	/// if `index` is out of range, see `KeyData::try_take_token` for a fallible version
 	pub fn take(self, index: usize) -> String {
 		self.0
 			.into_iter()
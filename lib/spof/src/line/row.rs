@@ -0,0 +1,42 @@
+use super::ParsedLine;
+use crate::Format;
+
+use std::ops::Deref;
+
+/// a `ParsedLine` with the `Format` of its keyword, to access its tokens by name
+///
+/// ```
+/// use spof::{ExpectedSize, Format, ParsedLine, Row};
+///
+/// let format = Format::new("X Y Z [W]", ExpectedSize::Range(3, 4));
+/// let line = ParsedLine::new(vec!["1".into(), "2".into(), "3".into()], 0);
+/// let row = Row::new(&line, &format);
+///
+/// assert_eq!(row.get("Y"), Some(&String::from("2")));
+/// assert_eq!(row.get("W"), None);
+/// assert_eq!(row.line.get(0), Some(&String::from("1")));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+	pub line: &'a ParsedLine,
+	pub format: &'a Format,
+}
+
+impl<'a> Row<'a> {
+	pub fn new(line: &'a ParsedLine, format: &'a Format) -> Self {
+		Self { line, format }
+	}
+
+	/// token named `name` in the format string
+	pub fn get(&self, name: &str) -> Option<&'a String> {
+		self.line.get(self.format.position(name)?)
+	}
+}
+
+impl Deref for Row<'_> {
+	type Target = ParsedLine;
+
+	fn deref(&self) -> &Self::Target {
+		self.line
+	}
+}
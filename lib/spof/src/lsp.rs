@@ -0,0 +1,553 @@
+use crate::file::parse;
+use crate::{Constraint, FileDataKey};
+
+use hmerr::display::strip;
+use hmerr::parse::Wrong;
+use hmerr::{ParseFileError, Result};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+	DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+	PublishDiagnostics,
+};
+use lsp_types::request::{Completion, HoverRequest, Request as _};
+use lsp_types::{
+	CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, Diagnostic,
+	DiagnosticRelatedInformation, DiagnosticSeverity, DidChangeTextDocumentParams,
+	DidCloseTextDocumentParams, DidOpenTextDocumentParams, Documentation, Hover, HoverContents,
+	HoverParams, HoverProviderCapability, Location, MarkupContent, MarkupKind, NumberOrString,
+	Position, PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+	TextDocumentSyncKind, Uri,
+};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+/// language server for the files described by the rules of `K`
+///
+/// * diagnostics - every line is checked with `ExpectedLine::check`,
+///   then the order and occurrence of the keywords
+/// * completion - keywords of the rules, and values of `Constraint::OneOf` tokens
+/// * hover - `ExpectedLine::help` of the keyword
+///
+/// # Example
+///
+/// ```no_run
+/// use spof::lsp::Server;
+/// use spof::rule;
+///
+/// rule!(
+///     enum ConfRule {
+///         Port => "port", "PORT", Fixed, Once, "the port to listen on",
+///     }
+/// );
+///
+/// fn main() -> hmerr::Result<()> {
+///     // talk to the editor on stdin/stdout
+///     Server::<ConfRule>::new(Some("#")).serve()
+/// }
+/// ```
+pub struct Server<K: FileDataKey> {
+	comment: Option<String>,
+	document: HashMap<Uri, String>,
+	_key: PhantomData<K>,
+}
+
+impl<K: FileDataKey> Server<K> {
+	pub fn new(comment: Option<&str>) -> Self {
+		Self {
+			comment: comment.map(String::from),
+			document: HashMap::new(),
+			_key: PhantomData,
+		}
+	}
+
+	pub fn capabilities() -> ServerCapabilities {
+		ServerCapabilities {
+			text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+			completion_provider: Some(CompletionOptions::default()),
+			hover_provider: Some(HoverProviderCapability::Simple(true)),
+			..Default::default()
+		}
+	}
+
+	/// run the server on stdin/stdout until the editor asks it to exit
+	pub fn serve(self) -> Result<()> {
+		let (connection, io_threads) = Connection::stdio();
+		self.run(connection)?;
+		io_threads.join()?;
+		Ok(())
+	}
+
+	/// initialize `connection` and answer its messages until the editor asks to exit
+	///
+	/// only a broken connection stops the server,
+	/// a malformed request is answered with an error and a malformed notification is skipped
+	pub fn run(mut self, connection: Connection) -> Result<()> {
+		connection.initialize(serde_json::to_value(Self::capabilities())?)?;
+
+		for msg in &connection.receiver {
+			match msg {
+				Message::Request(req) => {
+					if connection.handle_shutdown(&req)? {
+						return Ok(());
+					}
+					connection.sender.send(self.request(req).into())?;
+				}
+				Message::Notification(n) => {
+					if let Some(n) = self.notification(n) {
+						connection.sender.send(n.into())?;
+					}
+				}
+				Message::Response(_) => {}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn request(&self, req: Request) -> Response {
+		let id = req.id.clone();
+
+		match req.method.as_str() {
+			Completion::METHOD => {
+				let p = match req.extract::<CompletionParams>(Completion::METHOD) {
+					Ok((_, p)) => p.text_document_position,
+					Err(e) => return invalid_params(id, e),
+				};
+				let item = self
+					.document
+					.get(&p.text_document.uri)
+					.map(|text| completion::<K>(text, p.position))
+					.unwrap_or_default();
+				Response::new_ok(id, item)
+			}
+			HoverRequest::METHOD => {
+				let p = match req.extract::<HoverParams>(HoverRequest::METHOD) {
+					Ok((_, p)) => p.text_document_position_params,
+					Err(e) => return invalid_params(id, e),
+				};
+				let hover = self
+					.document
+					.get(&p.text_document.uri)
+					.and_then(|text| hover::<K>(text, p.position));
+				Response::new_ok(id, hover)
+			}
+			method => Response::new_err(
+				id,
+				ErrorCode::MethodNotFound as i32,
+				format!("unsupported method {method}"),
+			),
+		}
+	}
+
+	/// return the diagnostics to publish, if any
+	fn notification(&mut self, n: Notification) -> Option<Notification> {
+		let uri = match n.method.as_str() {
+			DidOpenTextDocument::METHOD => {
+				let p: DidOpenTextDocumentParams = match n.extract(DidOpenTextDocument::METHOD) {
+					Ok(p) => p,
+					Err(e) => return skip(e),
+				};
+				self.document
+					.insert(p.text_document.uri.clone(), p.text_document.text);
+				p.text_document.uri
+			}
+			DidChangeTextDocument::METHOD => {
+				let p: DidChangeTextDocumentParams = match n.extract(DidChangeTextDocument::METHOD)
+				{
+					Ok(p) => p,
+					Err(e) => return skip(e),
+				};
+				// full synchronization, the last change is the whole document
+				if let Some(change) = p.content_changes.into_iter().last() {
+					self.document
+						.insert(p.text_document.uri.clone(), change.text);
+				}
+				p.text_document.uri
+			}
+			DidCloseTextDocument::METHOD => {
+				let p: DidCloseTextDocumentParams = match n.extract(DidCloseTextDocument::METHOD) {
+					Ok(p) => p,
+					Err(e) => return skip(e),
+				};
+				self.document.remove(&p.text_document.uri);
+				p.text_document.uri
+			}
+			_ => return None,
+		};
+
+		let diagnostics = self
+			.document
+			.get(&uri)
+			.map(|text| diagnostics::<K>(&uri, text, self.comment.as_deref()))
+			.unwrap_or_default();
+
+		Some(Notification::new(
+			PublishDiagnostics::METHOD.to_string(),
+			PublishDiagnosticsParams::new(uri, diagnostics, None),
+		))
+	}
+}
+
+fn invalid_params(id: RequestId, e: impl Display) -> Response {
+	Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string())
+}
+
+/// a notification has no response, the error can only be logged (on stderr, stdout is the editor's)
+fn skip(e: impl Display) -> Option<Notification> {
+	hmerr::warn!("skipped notification: {e}");
+	None
+}
+
+/// check every line of `text`, then the order and occurrence of the keywords
+///
+/// occurrence is only checked when every line is valid,
+/// an invalid line would otherwise also be reported as a missing keyword
+pub fn diagnostics<K: FileDataKey>(
+	uri: &Uri,
+	text: &str,
+	comment: Option<&str>,
+) -> Vec<Diagnostic> {
+	let name = uri.as_str();
+	let mut data = K::build();
+	let mut raw: Vec<String> = Vec::new();
+	let mut error: Vec<Box<dyn Error>> = Vec::new();
+
+	for (i, line) in text.lines().enumerate() {
+		raw.push(line.to_string());
+		match parse(name, comment, &data, line.to_string(), i) {
+			Ok(Some((key, pl))) => data[key].data.push(pl),
+			Ok(None) => {}
+			Err(e) => error.push(e),
+		}
+	}
+
+	if let Err(e) = data.check_order(name, &raw) {
+		error.push(e);
+	}
+	if error.is_empty()
+		&& let Err(e) = data.check_occurrence(name)
+	{
+		error.push(e);
+	}
+
+	error
+		.iter()
+		.map(|e| diagnostic(uri, &raw, e.as_ref()))
+		.collect()
+}
+
+fn diagnostic(uri: &Uri, raw: &[String], e: &(dyn Error + 'static)) -> Diagnostic {
+	let Some(pfe) = e.downcast_ref::<ParseFileError>() else {
+		return Diagnostic {
+			range: Range::default(),
+			severity: Some(DiagnosticSeverity::ERROR),
+			source: Some(String::from("spof")),
+			message: strip(&e.to_string()),
+			..Default::default()
+		};
+	};
+
+	// the conflicting line of an order error
+	let related = pfe
+		.source
+		.as_ref()
+		.and_then(|s| s.downcast_ref::<ParseFileError>())
+		.filter(|s| s.line.as_ref().is_some_and(|l| l.index.is_some()))
+		.map(|s| {
+			vec![DiagnosticRelatedInformation {
+				location: Location::new(uri.clone(), range(raw, s)),
+				message: strip(&s.error),
+			}]
+		});
+
+	Diagnostic {
+		range: range(raw, pfe),
+		severity: Some(DiagnosticSeverity::ERROR),
+		source: Some(String::from("spof")),
+		code: pfe.code.clone().map(NumberOrString::String),
+		message: strip(&pfe.error),
+		related_information: related,
+		..Default::default()
+	}
+}
+
+/// range of the wrong part of the line of `e`, the whole line if nothing is wrong
+fn range(raw: &[String], e: &ParseFileError) -> Range {
+	let Some((line, index)) = e.line.as_ref().and_then(|l| Some((l, l.index?))) else {
+		return Range::default();
+	};
+	let content = raw.get(index).map(String::as_str).unwrap_or(&line.line);
+
+	let byte = line
+		.wrong
+		.iter()
+		.filter_map(|w| match w {
+			Wrong::Bit((start, len)) => Some((*start, start + len)),
+			Wrong::Str(s) if !s.is_empty() => content.find(s.as_str()).map(|i| (i, i + s.len())),
+			Wrong::Str(_) => None,
+			Wrong::Label(l) if l.primary => Some((l.range.start, l.range.end)),
+			Wrong::Label(_) => None,
+		})
+		.reduce(|(s1, e1), (s2, e2)| (s1.min(s2), e1.max(e2)))
+		.unwrap_or((0, content.len()));
+
+	Range::new(
+		Position::new(index as u32, utf16_column(content, byte.0)),
+		Position::new(index as u32, utf16_column(content, byte.1)),
+	)
+}
+
+/// keywords at the start of a line, values of the `Constraint::OneOf` token under the cursor
+pub fn completion<K: FileDataKey>(text: &str, position: Position) -> Vec<CompletionItem> {
+	let line = text.lines().nth(position.line as usize).unwrap_or_default();
+	let before = &line[..byte_index(line, position.character)];
+	let mut word = before.split_whitespace();
+	let count = word.clone().count() - usize::from(before.ends_with(|c: char| !c.is_whitespace()));
+
+	let rule = K::build();
+
+	if count == 0 {
+		return rule
+			.iter()
+			.map(|kd| CompletionItem {
+				label: kd.rule.k.keyword.clone(),
+				kind: Some(CompletionItemKind::KEYWORD),
+				detail: Some(kd.rule.k.desc.clone()),
+				documentation: Some(Documentation::String(strip(&kd.rule.help()))),
+				..Default::default()
+			})
+			.collect();
+	}
+
+	let Some(Ok(k)) = word.next().map(str::parse::<K>) else {
+		return Vec::new();
+	};
+	match rule[k].rule.format.constraint(count - 1) {
+		Some(Constraint::OneOf(value)) => value
+			.iter()
+			.map(|v| CompletionItem {
+				label: v.clone(),
+				kind: Some(CompletionItemKind::VALUE),
+				..Default::default()
+			})
+			.collect(),
+		_ => Vec::new(),
+	}
+}
+
+/// help of the keyword under the cursor
+pub fn hover<K: FileDataKey>(text: &str, position: Position) -> Option<Hover> {
+	let line = text.lines().nth(position.line as usize)?;
+	let start = line.len() - line.trim_start().len();
+	let keyword = line[start..].split_whitespace().next()?;
+	let end = start + keyword.len();
+
+	let cursor = byte_index(line, position.character);
+	if cursor < start || cursor > end {
+		return None;
+	}
+
+	let k = keyword.parse::<K>().ok()?;
+	let rule = &K::build()[k].rule;
+
+	Some(Hover {
+		contents: HoverContents::Markup(MarkupContent {
+			kind: MarkupKind::PlainText,
+			value: strip(&rule.help()),
+		}),
+		range: Some(Range::new(
+			Position::new(position.line, utf16_column(line, start)),
+			Position::new(position.line, utf16_column(line, end)),
+		)),
+	})
+}
+
+/// LSP columns count UTF-16 code units
+fn utf16_column(line: &str, byte: usize) -> u32 {
+	let mut byte = byte.min(line.len());
+	while !line.is_char_boundary(byte) {
+		byte -= 1;
+	}
+	line[..byte].encode_utf16().count() as u32
+}
+
+fn byte_index(line: &str, utf16: u32) -> usize {
+	let mut n = 0;
+	for (i, c) in line.char_indices() {
+		if n >= utf16 as usize {
+			return i;
+		}
+		n += c.len_utf16();
+	}
+	line.len()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::rule;
+
+	use std::str::FromStr;
+
+	rule!(
+		enum RuleTest {
+			Name => "name", "NAME", Fixed, Once, "the name",
+			Mode => "mode", "MODE:fast|safe", Fixed, Optional, "the mode"; crate::Order::After(String::from("name")),
+		}
+	);
+
+	fn uri() -> Uri {
+		Uri::from_str("file:///test.conf").unwrap()
+	}
+
+	#[test]
+	fn test_diagnostics() {
+		let d = diagnostics::<RuleTest>(&uri(), "name é\nmode slow\nname x y", Some("#"));
+		assert_eq!(d.len(), 2);
+		assert_eq!(
+			d[0].range,
+			Range::new(Position::new(1, 5), Position::new(1, 9))
+		);
+		assert!(!d[0].message.contains('\x1b'));
+		assert_eq!(d[1].range.start.line, 2);
+
+		// missing `name`, no line to point to
+		let d = diagnostics::<RuleTest>(&uri(), "# nothing", Some("#"));
+		assert_eq!(d.len(), 1);
+		assert_eq!(d[0].range, Range::default());
+
+		// order error, with the conflicting line
+		let d = diagnostics::<RuleTest>(&uri(), "mode fast\nname x", Some("#"));
+		assert_eq!(d.len(), 1);
+		assert_eq!(d[0].range.start.line, 0);
+		assert!(d[0].related_information.is_some());
+		assert_eq!(
+			d[0].code,
+			Some(NumberOrString::String(crate::code::ORDER.to_string()))
+		);
+
+		assert!(diagnostics::<RuleTest>(&uri(), "name x\nmode fast", Some("#")).is_empty());
+	}
+
+	#[test]
+	fn test_completion() {
+		let label = |text: &str, line: u32, character: u32| -> Vec<String> {
+			completion::<RuleTest>(text, Position::new(line, character))
+				.into_iter()
+				.map(|c| c.label)
+				.collect()
+		};
+
+		assert_eq!(label("", 0, 0), ["name", "mode"]);
+		assert_eq!(label("name x\n  mo", 1, 4), ["name", "mode"]);
+		assert_eq!(label("mode ", 0, 5), ["fast", "safe"]);
+		assert_eq!(label("mode f", 0, 6), ["fast", "safe"]);
+		assert!(label("name ", 0, 5).is_empty());
+		assert!(label("unknown ", 0, 8).is_empty());
+	}
+
+	#[test]
+	fn test_hover() {
+		let h = hover::<RuleTest>("name x\n  mode fast", Position::new(1, 3)).unwrap();
+		let HoverContents::Markup(m) = h.contents else {
+			panic!("expected markup");
+		};
+		assert!(m.value.contains("the mode"));
+		assert!(m.value.contains("MODE:fast|safe"));
+		assert_eq!(
+			h.range,
+			Some(Range::new(Position::new(1, 2), Position::new(1, 6)))
+		);
+
+		assert!(hover::<RuleTest>("mode fast", Position::new(0, 7)).is_none());
+		assert!(hover::<RuleTest>("unknown", Position::new(0, 1)).is_none());
+	}
+
+	#[test]
+	fn test_server() {
+		let (server, client) = Connection::memory();
+		let thread = std::thread::spawn(move || {
+			Server::<RuleTest>::new(Some("#"))
+				.run(server)
+				.map_err(|e| e.to_string())
+		});
+
+		let send = |msg: Message| client.sender.send(msg).unwrap();
+		let request = |id: i32, method: &str, params: serde_json::Value| {
+			send(Request::new(id.into(), method.to_string(), params).into());
+			match client.receiver.recv().unwrap() {
+				Message::Response(r) => r.result.unwrap(),
+				msg => panic!("unexpected message {msg:?}"),
+			}
+		};
+		let notify = |method: &str, params: serde_json::Value| {
+			send(Notification::new(method.to_string(), params).into());
+		};
+
+		let init = request(1, "initialize", serde_json::json!({ "capabilities": {} }));
+		assert!(init["capabilities"]["hoverProvider"].as_bool().unwrap());
+		notify("initialized", serde_json::json!({}));
+
+		notify(
+			DidOpenTextDocument::METHOD,
+			serde_json::json!({
+				"textDocument": { "uri": uri(), "languageId": "conf", "version": 1, "text": "mode slow" }
+			}),
+		);
+		let Message::Notification(n) = client.receiver.recv().unwrap() else {
+			panic!("expected diagnostics");
+		};
+		let p: PublishDiagnosticsParams = serde_json::from_value(n.params).unwrap();
+		assert_eq!(p.diagnostics.len(), 1);
+
+		let hover = request(
+			2,
+			HoverRequest::METHOD,
+			serde_json::json!({
+				"textDocument": { "uri": uri() },
+				"position": { "line": 0, "character": 1 }
+			}),
+		);
+		assert!(
+			hover["contents"]["value"]
+				.as_str()
+				.unwrap()
+				.contains("the mode")
+		);
+
+		// malformed messages do not stop the server
+		notify(
+			DidOpenTextDocument::METHOD,
+			serde_json::json!({ "textDocument": 42 }),
+		);
+		send(
+			Request::new(
+				3.into(),
+				HoverRequest::METHOD.to_string(),
+				serde_json::json!({}),
+			)
+			.into(),
+		);
+		let Message::Response(r) = client.receiver.recv().unwrap() else {
+			panic!("expected a response");
+		};
+		assert_eq!(r.id, 3.into());
+		assert_eq!(r.error.unwrap().code, ErrorCode::InvalidParams as i32);
+		let completion = request(
+			4,
+			Completion::METHOD,
+			serde_json::json!({
+				"textDocument": { "uri": uri() },
+				"position": { "line": 0, "character": 0 }
+			}),
+		);
+		assert!(completion.as_array().is_some_and(|c| !c.is_empty()));
+
+		send(Request::new(5.into(), "shutdown".to_string(), ()).into());
+		notify("exit", serde_json::Value::Null);
+		thread.join().unwrap().unwrap();
+	}
+}
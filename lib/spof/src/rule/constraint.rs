@@ -0,0 +1,130 @@
+use crate::Pattern;
+
+use ansi::abbrev::{B, D, G};
+
+use std::fmt::{Display, Formatter};
+
+/// restriction on the value of a single token
+///
+/// in a format string, a token can be written as:
+/// * `fast|safe|debug` - the token must be one of the listed values
+/// * `NAME:fast|safe|debug` - same, with a name for the token
+/// * `NAME:/PATTERN/` - the token must match the `Pattern` (ex: `COLOR:/#[0-9a-fA-F]{6}/`)
+///
+/// any other token is not constrained, even with a `:` (ex: `HOST:PORT`)
+///
+/// the token can still be surrounded by `[` `]` to show that it is optional (ex: `[MODE:fast|safe]`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constraint {
+	OneOf(Vec<String>),
+	Pattern(Pattern),
+}
+
+impl Constraint {
+	pub fn one_of(value: impl IntoIterator<Item = impl Into<String>>) -> Self {
+		Self::OneOf(value.into_iter().map(Into::into).collect())
+	}
+
+	pub fn pattern(pattern: impl Into<String>) -> Self {
+		Self::Pattern(Pattern::new(pattern))
+	}
+
+	/// parse a single token of a format string,
+	/// return `None` if the token does not define any constraint
+	pub fn parse(token: &str) -> Option<Self> {
+		let token = token
+			.strip_prefix('[')
+			.and_then(|t| t.strip_suffix(']'))
+			.unwrap_or(token);
+
+		match token.split_once(':') {
+			Some((_, c)) if c.len() > 2 && c.starts_with('/') && c.ends_with('/') => {
+				Some(Self::pattern(&c[1..c.len() - 1]))
+			}
+			Some((_, c)) if c.contains('|') => Some(Self::one_of(c.split('|'))),
+			_ if token.contains('|') => Some(Self::one_of(token.split('|'))),
+			_ => None,
+		}
+	}
+
+	pub fn matches(&self, token: &str) -> bool {
+		match self {
+			Self::OneOf(value) => value.iter().any(|v| v == token),
+			Self::Pattern(pattern) => pattern.matches(token),
+		}
+	}
+
+	pub fn help(&self) -> String {
+		match self {
+			Self::OneOf(value) => format!(
+				"the value must be one of:\n{value_list}",
+				value_list = value
+					.iter()
+					.map(|v| format!("\t- {B}{G}{v}{D}"))
+					.collect::<Vec<String>>()
+					.join("\n")
+			),
+			Self::Pattern(pattern) => {
+				format!("the value must match the pattern `{B}{G}{pattern}{D}`")
+			}
+		}
+	}
+}
+
+impl Display for Constraint {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::OneOf(value) => write!(f, "{}", value.join("|")),
+			Self::Pattern(pattern) => write!(f, "{pattern}"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_constraint_parse() {
+		assert_eq!(Constraint::parse("X"), None);
+		assert_eq!(Constraint::parse("[W]"), None);
+		assert_eq!(Constraint::parse("NAME:"), None);
+		assert_eq!(
+			Constraint::parse("fast|safe|debug"),
+			Some(Constraint::one_of(["fast", "safe", "debug"]))
+		);
+		assert_eq!(
+			Constraint::parse("MODE:fast|safe"),
+			Some(Constraint::one_of(["fast", "safe"]))
+		);
+		assert_eq!(
+			Constraint::parse("[MODE:fast|safe]"),
+			Some(Constraint::one_of(["fast", "safe"]))
+		);
+		assert_eq!(
+			Constraint::parse("COLOR:/#[0-9a-f]{6}/"),
+			Some(Constraint::pattern("#[0-9a-f]{6}"))
+		);
+		assert_eq!(
+			Constraint::parse("[PATH:/*.conf/]"),
+			Some(Constraint::pattern("*.conf"))
+		);
+
+		// placeholders, not constraints
+		assert_eq!(Constraint::parse("HOST:PORT"), None);
+		assert_eq!(Constraint::parse("COLOR:#[0-9a-f]{6}"), None);
+		assert_eq!(Constraint::parse("NAME://"), None);
+	}
+
+	#[test]
+	fn test_constraint_matches() {
+		let c = Constraint::one_of(["fast", "safe"]);
+		assert!(c.matches("fast"));
+		assert!(!c.matches("fas"));
+		assert!(!c.matches("debug"));
+
+		let c = Constraint::pattern("[0-9]{1,3}");
+		assert!(c.matches("255"));
+		assert!(!c.matches("2555"));
+	}
+}
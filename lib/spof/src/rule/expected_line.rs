@@ -1,4 +1,4 @@
-use crate::{Format, Keyword, Occurrence};
+use crate::{Format, Keyword, Occurrence, Order, code};
 
 use ansi::abbrev::{B, D, G, M, R, Y};
 use hmerr::{Result, pfe, ple, pwe};
@@ -8,6 +8,7 @@ pub struct ExpectedLine {
 	pub k: Keyword,
 	pub format: Format,
 	pub occurrence: Occurrence,
+	pub order: Vec<Order>,
 }
 
 impl ExpectedLine {
@@ -16,30 +17,74 @@ impl ExpectedLine {
 			k,
 			format,
 			occurrence,
+			order: Vec::new(),
 		}
 	}
 
+	/// add an ordering constraint, checked once the whole file is parsed
+	pub fn with_order(mut self, order: Order) -> Self {
+		self.order.push(order);
+		self
+	}
+
 	pub fn check<T>(
 		&self,
 		file_name: &str,
 		line: String,
 		token: &[T],
 		line_index: usize,
-	) -> Result<()> {
-		match self.format.check(token) {
-			Ok(_) => Ok(()),
-			Err((expected, got)) => {
-				let line_len = line.len();
+	) -> Result<()>
+	where
+		T: AsRef<str>,
+	{
+		if let Err((expected, got)) = self.format.check(token) {
+			if let Some(name) = self.format.missing(got) {
+				// caret right after the last token (the keyword is the first word of the line)
+				let end = word_position(&line, got)
+					.map(|(start, len)| start + len)
+					.unwrap_or(line.len());
 				pfe!(
-					format!("expected {B}{G}{expected}{D} token after {B}{Y}{keyword}{D}, got {B}{R}{got}{D}",
+					format!("missing {B}{R}`{name}`{D} after {B}{Y}{keyword}{D}, expected {B}{G}{expected}{D} token, got {B}{R}{got}{D}",
 						keyword=self.k.keyword,
 					),
 					h:self.help(),
 					f:file_name,
-					l:ple!(line, i:line_index, w:pwe!((0, line_len)))
+					l:ple!(line.clone(), i:line_index, w:pwe!((end, 1))),
+					c:code::MISSING_TOKEN
 				)?
 			}
+
+			let line_len = line.len();
+			pfe!(
+				format!("expected {B}{G}{expected}{D} token after {B}{Y}{keyword}{D}, got {B}{R}{got}{D}",
+					keyword=self.k.keyword,
+				),
+				h:self.help(),
+				f:file_name,
+				l:ple!(line.clone(), i:line_index, w:pwe!((0, line_len))),
+				c:code::TOKEN_COUNT
+			)?
+		}
+
+		if let Err((i, constraint)) = self.format.check_value(token) {
+			let got = token[i].as_ref();
+			// the keyword is the first word of the line
+			let wrong = match word_position(&line, i + 1) {
+				Some((start, _)) => (start, got.len()),
+				None => (0, line.len()),
+			};
+			pfe!(
+				format!("invalid value {B}{R}{got}{D} for {B}{Y}{keyword}{D}, expected {B}{G}{constraint}{D}",
+					keyword=self.k.keyword,
+				),
+				h:format!("{help}\n{constraint_help}", help=self.help(), constraint_help=constraint.help()),
+				f:file_name,
+				l:ple!(line, i:line_index, w:pwe!(wrong)),
+				c:code::INVALID_VALUE
+			)?
 		}
+
+		Ok(())
 	}
 
 	pub fn help(&self) -> String {
@@ -53,6 +98,31 @@ the line must follow the format: `{B}{keyword} {M}{format}{D}`",
 	}
 }
 
+/// byte position `(start, len)` of the `n`th whitespace separated word of `line`
+fn word_position(line: &str, n: usize) -> Option<(usize, usize)> {
+	let mut word = 0;
+	let mut start: Option<usize> = None;
+
+	for (i, c) in line
+		.char_indices()
+		.chain(std::iter::once((line.len(), ' ')))
+	{
+		match (c.is_whitespace(), start) {
+			(false, None) => start = Some(i),
+			(true, Some(s)) => {
+				if word == n {
+					return Some((s, i - s));
+				}
+				word += 1;
+				start = None;
+			}
+			_ => {}
+		}
+	}
+
+	None
+}
+
 #[cfg(test)]
 mod test {
 	use super::*;
@@ -87,4 +157,74 @@ mod test {
 				.is_err()
 		);
 	}
+
+	#[test]
+	fn test_expected_line_constraint() {
+		let el = ExpectedLine::new(
+			Keyword::new("mode", "the mode"),
+			Format::new("fast|safe|debug", ExpectedSize::Fixed),
+			Occurrence::Once,
+		);
+
+		assert!(
+			el.check("file_name", "mode safe".to_string(), &["safe"], 0)
+				.is_ok()
+		);
+
+		let e = el
+			.check(
+				"file_name",
+				"  mode\tslow # comment".to_string(),
+				&["slow"],
+				3,
+			)
+			.unwrap_err();
+		let e = e.downcast_ref::<hmerr::ParseFileError>().unwrap();
+		let line = e.line.as_ref().unwrap();
+		assert_eq!(line.index, Some(3));
+		assert!(matches!(line.wrong[..], [hmerr::parse::Wrong::Bit((7, 4))]));
+		assert!(e.help.as_ref().unwrap().contains("debug"));
+	}
+
+	#[test]
+	fn test_expected_line_missing() {
+		let el = ExpectedLine::new(
+			Keyword::new("position", "the position"),
+			Format::new("X Y Z [W]", ExpectedSize::Range(3, 4)),
+			Occurrence::Once,
+		);
+
+		let e = el
+			.check("file_name", "position 1 2 # 3".to_string(), &["1", "2"], 0)
+			.unwrap_err();
+		let e = e.downcast_ref::<hmerr::ParseFileError>().unwrap();
+		assert!(e.error.contains("`Z`"));
+		let line = e.line.as_ref().unwrap();
+		assert!(matches!(
+			line.wrong[..],
+			[hmerr::parse::Wrong::Bit((12, 1))]
+		));
+
+		let e = el
+			.check(
+				"file_name",
+				"position 1 2 3 4 5".to_string(),
+				&["1", "2", "3", "4", "5"],
+				0,
+			)
+			.unwrap_err();
+		let e = e.downcast_ref::<hmerr::ParseFileError>().unwrap();
+		assert!(!e.error.contains("missing"));
+	}
+
+	#[test]
+	fn test_word_position() {
+		assert_eq!(word_position("", 0), None);
+		assert_eq!(word_position("a", 0), Some((0, 1)));
+		assert_eq!(word_position("a", 1), None);
+		assert_eq!(word_position("  key\tval  ue ", 0), Some((2, 3)));
+		assert_eq!(word_position("  key\tval  ue ", 1), Some((6, 3)));
+		assert_eq!(word_position("  key\tval  ue ", 2), Some((11, 2)));
+		assert_eq!(word_position("é ü", 1), Some((3, 2)));
+	}
 }
@@ -1,9 +1,13 @@
+use crate::Constraint;
+
 use std::fmt::{Debug, Display, Formatter};
 
 #[derive(Debug)]
 pub struct Format {
 	pub token: String,
 	size: Size,
+	name: Vec<String>,
+	constraint: Vec<Option<Constraint>>,
 }
 
 pub enum Size {
@@ -33,15 +37,92 @@ impl From<(ExpectedSize, &str)> for Size {
 }
 
 impl Format {
+	/// `token` is the format string, one word per token
+	///
+	/// a token is constrained only by `a|b` or `NAME:a|b` (one of the values)
+	/// and `NAME:/PATTERN/` (matches the `Pattern`), see `Constraint`,
+	/// any other token (`X`, `[X]`, `HOST:PORT`) accepts any value
 	pub fn new(token: impl Into<String>, expected_size: ExpectedSize) -> Self {
 		let token: String = token.into();
 		let size: Size = (expected_size, token.as_str()).into();
-		Self { token, size }
+		let name = token.split_whitespace().map(position_name).collect();
+		let constraint = token.split_whitespace().map(Constraint::parse).collect();
+		Self {
+			token,
+			size,
+			name,
+			constraint,
+		}
+	}
+
+	/// name of the token at `index`, from the format string
+	///
+	/// `X` for `X`, `[X]`, `X:a|b` and `X:/PATTERN/`,
+	/// the whole token for an unnamed `a|b` or an unconstrained `X:Y`
+	pub fn name(&self, index: usize) -> Option<&str> {
+		self.name.get(index).map(String::as_str)
+	}
+
+	/// index of the token named `name`
+	pub fn position(&self, name: &str) -> Option<usize> {
+		self.name.iter().position(|n| n == name)
+	}
+
+	/// name of the first required token missing from a line of `got` tokens
+	pub fn missing(&self, got: usize) -> Option<&str> {
+		match self.size {
+			Size::Fixed(min) | Size::Range(min, _) if got < min => self.name(got),
+			_ => None,
+		}
+	}
+
+	/// restrict the value of the token at `index`
+	pub fn with_constraint(mut self, index: usize, constraint: Constraint) -> Self {
+		if self.constraint.len() <= index {
+			self.constraint.resize(index + 1, None);
+		}
+		self.constraint[index] = Some(constraint);
+		self
+	}
+
+	pub fn constraint(&self, index: usize) -> Option<&Constraint> {
+		self.constraint.get(index).and_then(Option::as_ref)
 	}
 
 	pub fn check<T>(&self, token: &[T]) -> std::result::Result<(), (String, usize)> {
 		self.size.check(token.len())
 	}
+
+	/// check every token against its constraint,
+	/// return the index of the first invalid token and the constraint it breaks
+	pub fn check_value<T>(&self, token: &[T]) -> std::result::Result<(), (usize, &Constraint)>
+	where
+		T: AsRef<str>,
+	{
+		for (i, t) in token.iter().enumerate() {
+			if let Some(c) = self.constraint(i)
+				&& !c.matches(t.as_ref())
+			{
+				return Err((i, c));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+fn position_name(token: &str) -> String {
+	let token = token
+		.strip_prefix('[')
+		.and_then(|t| t.strip_suffix(']'))
+		.unwrap_or(token);
+
+	match token.split_once(':') {
+		Some((name, _)) if !name.is_empty() && Constraint::parse(token).is_some() => {
+			name.to_string()
+		}
+		_ => token.to_string(),
+	}
 }
 
 impl Display for Size {
@@ -192,6 +273,28 @@ mod test {
 		}
 	}
 
+	#[test]
+	fn test_format_name() {
+		let format = Format::new(
+			"X [Y] MODE:fast|safe COLOR:/#[0-9a-f]{6}/ on|off [W] HOST:PORT",
+			ExpectedSize::Range(4, 7),
+		);
+		assert_eq!(format.name(0), Some("X"));
+		assert_eq!(format.name(1), Some("Y"));
+		assert_eq!(format.name(2), Some("MODE"));
+		assert_eq!(format.name(3), Some("COLOR"));
+		assert_eq!(format.name(4), Some("on|off"));
+		assert_eq!(format.name(5), Some("W"));
+		assert_eq!(format.name(6), Some("HOST:PORT"));
+		assert_eq!(format.name(7), None);
+		assert_eq!(format.position("COLOR"), Some(3));
+		assert_eq!(format.position("Z"), None);
+
+		assert_eq!(format.missing(2), Some("MODE"));
+		assert_eq!(format.missing(4), None);
+		assert_eq!(Format::new("X", ExpectedSize::Undefined).missing(0), None);
+	}
+
 	#[test]
 	fn test_format_fixed() {
 		let token = String::from("test");
@@ -248,4 +351,33 @@ mod test {
 		assert!(format.check(&vec![token.clone(); 2]).is_err());
 		assert!(format.check(&vec![token; 3]).is_err());
 	}
+
+	#[test]
+	fn test_format_constraint() {
+		let format = Format::new("MODE:fast|safe [LEVEL:/[0-9]/]", ExpectedSize::Range(1, 2));
+		assert_eq!(format.size, Size::Range(1, 2));
+		assert!(format.check_value(&["fast"]).is_ok());
+		assert!(format.check_value(&["safe", "4"]).is_ok());
+		assert_eq!(format.check_value(&["slow"]).unwrap_err().0, 0);
+		assert_eq!(format.check_value(&["fast", "42"]).unwrap_err().0, 1);
+
+		// a `:` alone does not constrain the token
+		let format = Format::new("HOST:PORT KEY:VALUE", ExpectedSize::Fixed);
+		assert!(format.constraint(0).is_none());
+		assert!(format.constraint(1).is_none());
+		assert!(format.check_value(&["localhost:80", "a:b"]).is_ok());
+
+		let format = Format::new("R G B", ExpectedSize::Fixed)
+			.with_constraint(1, Constraint::pattern("[0-9]{1,3}"));
+		assert!(format.constraint(0).is_none());
+		assert!(format.constraint(1).is_some());
+		assert!(format.constraint(42).is_none());
+		assert!(format.check_value(&["x", "255", "x"]).is_ok());
+		assert_eq!(format.check_value(&["0", "x", "0"]).unwrap_err().0, 1);
+
+		let format =
+			Format::new("X", ExpectedSize::Undefined).with_constraint(3, Constraint::one_of(["a"]));
+		assert!(format.check_value(&["x", "x", "x", "a", "x"]).is_ok());
+		assert!(format.check_value(&["x", "x", "x", "b"]).is_err());
+	}
 }
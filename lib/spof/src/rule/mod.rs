@@ -1,4 +1,7 @@
+pub mod constraint;
 pub mod expected_line;
 pub mod format;
 pub mod keyword;
 pub mod occurrence;
+pub mod order;
+pub mod pattern;
@@ -51,6 +51,26 @@ impl Occurrence {
 		}
 	}
 
+	/// whether the keyword can appear on more than one line
+	pub fn is_repeatable(&self) -> bool {
+		match self {
+			Occurrence::Once | Occurrence::Optional => false,
+			Occurrence::ZeroOrMore | Occurrence::OneOrMore => true,
+			Occurrence::Exactly(x) => *x > 1,
+			Occurrence::Range(_, end) => *end > 1,
+		}
+	}
+
+	/// maximum number of lines allowed, `None` if unbounded
+	pub fn max(&self) -> Option<usize> {
+		match self {
+			Occurrence::Once | Occurrence::Optional => Some(1),
+			Occurrence::ZeroOrMore | Occurrence::OneOrMore => None,
+			Occurrence::Exactly(x) => Some(*x),
+			Occurrence::Range(_, end) => Some(*end),
+		}
+	}
+
 	pub fn check(&self, n: usize) -> Result<(), String> {
 		if self.in_range(n) {
 			Ok(())
@@ -206,4 +226,26 @@ mod test {
 		assert!(occ.in_range(42));
 		assert!(!occ.in_range(84));
 	}
+
+	#[test]
+	fn test_occurrence_is_repeatable() {
+		assert!(!Occurrence::Once.is_repeatable());
+		assert!(!Occurrence::Optional.is_repeatable());
+		assert!(Occurrence::ZeroOrMore.is_repeatable());
+		assert!(Occurrence::OneOrMore.is_repeatable());
+		assert!(!Occurrence::Exactly(1).is_repeatable());
+		assert!(Occurrence::Exactly(42).is_repeatable());
+		assert!(!Occurrence::Range(0, 1).is_repeatable());
+		assert!(Occurrence::Range(1, 42).is_repeatable());
+	}
+
+	#[test]
+	fn test_occurrence_max() {
+		assert_eq!(Occurrence::Once.max(), Some(1));
+		assert_eq!(Occurrence::Optional.max(), Some(1));
+		assert_eq!(Occurrence::ZeroOrMore.max(), None);
+		assert_eq!(Occurrence::OneOrMore.max(), None);
+		assert_eq!(Occurrence::Exactly(42).max(), Some(42));
+		assert_eq!(Occurrence::Range(1, 42).max(), Some(42));
+	}
 }
@@ -0,0 +1,156 @@
+use std::fmt::{Display, Formatter};
+
+/// where a keyword is allowed to appear in the file, relative to the other keywords
+///
+/// every line of the keyword must respect the constraint
+#[derive(Debug, Clone, PartialEq)]
+pub enum Order {
+	First,             // before any other keyword
+	Last,              // after any other keyword
+	Before(String),    // before the first line of keyword
+	After(String),     // after the first line of keyword
+	BeforeLine(usize), // before line index
+}
+
+impl Order {
+	pub fn before(keyword: impl Into<String>) -> Self {
+		Self::Before(keyword.into())
+	}
+
+	pub fn after(keyword: impl Into<String>) -> Self {
+		Self::After(keyword.into())
+	}
+
+	/// check the line indexes of `keyword` against the line indexes of every keyword of the file
+	///
+	/// on error, return the misplaced line index and the line index it conflicts with (if any)
+	pub fn check(
+		&self,
+		keyword: &str,
+		line: &[usize],
+		file: &[(&str, Vec<usize>)],
+	) -> Result<(), (usize, Option<usize>)> {
+		// first line of the keyword `Before` or `After` refers to
+		let first = match self {
+			Self::Before(k) | Self::After(k) => file
+				.iter()
+				.filter(|(fk, _)| fk == k)
+				.flat_map(|(_, l)| l.iter().copied())
+				.min(),
+			_ => None,
+		};
+		let mut other: Vec<usize> = file
+			.iter()
+			.filter(|(k, _)| *k != keyword)
+			.flat_map(|(_, l)| l.iter().copied())
+			.collect();
+		other.sort_unstable();
+
+		for &l in line {
+			let conflict = match self {
+				Self::First => other.first().copied().filter(|o| *o < l),
+				Self::Last => other.iter().copied().find(|o| *o > l),
+				Self::Before(_) => first.filter(|o| *o < l),
+				Self::After(_) => match first {
+					Some(o) if o < l => None,
+					o => return Err((l, o)),
+				},
+				Self::BeforeLine(n) if l >= *n => return Err((l, None)),
+				Self::BeforeLine(_) => None,
+			};
+
+			if let Some(conflict) = conflict {
+				return Err((l, Some(conflict)));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl Display for Order {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::First => write!(f, "first"),
+			Self::Last => write!(f, "last"),
+			Self::Before(k) => write!(f, "before `{k}`"),
+			Self::After(k) => write!(f, "after `{k}`"),
+			Self::BeforeLine(n) => write!(f, "before line {n}"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn file() -> Vec<(&'static str, Vec<usize>)> {
+		vec![
+			("header", vec![0]),
+			("material", vec![1, 4]),
+			("face", vec![2, 3, 5]),
+			("end", vec![6]),
+		]
+	}
+
+	#[test]
+	fn test_order_first() {
+		let f = file();
+		assert!(Order::First.check("header", &[0], &f).is_ok());
+		assert_eq!(
+			Order::First.check("material", &[1, 4], &f),
+			Err((1, Some(0)))
+		);
+		assert!(
+			Order::First
+				.check("header", &[0], &[("header", vec![0])])
+				.is_ok()
+		);
+	}
+
+	#[test]
+	fn test_order_last() {
+		let f = file();
+		assert!(Order::Last.check("end", &[6], &f).is_ok());
+		assert_eq!(Order::Last.check("face", &[2, 3, 5], &f), Err((2, Some(4))));
+	}
+
+	#[test]
+	fn test_order_before() {
+		let f = file();
+		assert!(Order::before("face").check("header", &[0], &f).is_ok());
+		assert!(Order::before("unknown").check("header", &[0], &f).is_ok());
+		assert_eq!(
+			Order::before("face").check("material", &[1, 4], &f),
+			Err((4, Some(2)))
+		);
+	}
+
+	#[test]
+	fn test_order_after() {
+		let f = file();
+		assert!(
+			Order::after("material")
+				.check("face", &[2, 3, 5], &f)
+				.is_ok()
+		);
+		assert_eq!(
+			Order::after("face").check("material", &[1, 4], &f),
+			Err((1, Some(2)))
+		);
+		assert_eq!(
+			Order::after("unknown").check("face", &[2, 3, 5], &f),
+			Err((2, None))
+		);
+	}
+
+	#[test]
+	fn test_order_before_line() {
+		let f = file();
+		assert!(Order::BeforeLine(6).check("face", &[2, 3, 5], &f).is_ok());
+		assert_eq!(
+			Order::BeforeLine(5).check("face", &[2, 3, 5], &f),
+			Err((5, None))
+		);
+	}
+}
@@ -0,0 +1,276 @@
+use std::fmt::{Display, Formatter};
+
+/// simple glob pattern a token must match
+///
+/// * `?` - any character
+/// * `*` - any sequence of characters (including none)
+/// * `[abc]`, `[a-z]` - any character of the class
+/// * `[!a-z]`, `[^a-z]` - any character not in the class
+/// * `{n}`, `{n,}`, `{n,m}` - repeat the previous atom (`n` times, `n` or more, between `n` and `m`)
+/// * `\c` - the character `c` as is
+///
+/// any other character matches itself,
+/// a malformed class or repetition is matched literally
+///
+/// # Example
+///
+/// ```
+/// use spof::Pattern;
+///
+/// let hex = Pattern::new("#[0-9a-fA-F]{6}");
+/// assert!(hex.matches("#ff0040"));
+/// assert!(!hex.matches("#ff004"));
+/// assert!(!hex.matches("ff0040"));
+///
+/// let ident = Pattern::new("[a-zA-Z_][a-zA-Z0-9_]{0,}");
+/// assert!(ident.matches("my_var42"));
+/// assert!(!ident.matches("42var"));
+///
+/// let obj = Pattern::new("*.obj");
+/// assert!(obj.matches("teapot.obj"));
+/// assert!(!obj.matches("teapot.mtl"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+	source: String,
+	atom: Vec<Quantified>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Quantified {
+	atom: Atom,
+	min: usize,
+	max: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Atom {
+	Any,
+	Char(char),
+	Class {
+		negated: bool,
+		range: Vec<(char, char)>,
+	},
+}
+
+impl Pattern {
+	pub fn new(source: impl Into<String>) -> Self {
+		let source: String = source.into();
+		let atom = compile(&source);
+		Self { source, atom }
+	}
+
+	pub fn matches(&self, token: &str) -> bool {
+		let token: Vec<char> = token.chars().collect();
+		match_from(&self.atom, &token)
+	}
+}
+
+impl Display for Pattern {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.source)
+	}
+}
+
+impl Atom {
+	fn matches(&self, c: char) -> bool {
+		match self {
+			Atom::Any => true,
+			Atom::Char(x) => *x == c,
+			Atom::Class { negated, range } => {
+				range.iter().any(|(start, end)| c >= *start && c <= *end) != *negated
+			}
+		}
+	}
+}
+
+fn compile(source: &str) -> Vec<Quantified> {
+	let chars: Vec<char> = source.chars().collect();
+	let mut atom: Vec<Quantified> = Vec::new();
+	let mut i = 0;
+
+	while i < chars.len() {
+		let (a, min, max, next) = match chars[i] {
+			'?' => (Atom::Any, 1, Some(1), i + 1),
+			'*' => (Atom::Any, 0, None, i + 1),
+			'\\' if i + 1 < chars.len() => (Atom::Char(chars[i + 1]), 1, Some(1), i + 2),
+			'[' => match compile_class(&chars, i) {
+				Some((class, next)) => (class, 1, Some(1), next),
+				None => (Atom::Char('['), 1, Some(1), i + 1),
+			},
+			c => (Atom::Char(c), 1, Some(1), i + 1),
+		};
+		let (min, max, next) = compile_repeat(&chars, next).unwrap_or((min, max, next));
+
+		atom.push(Quantified { atom: a, min, max });
+		i = next;
+	}
+
+	atom
+}
+
+/// `chars[start]` is `[`, return the class and the index after `]`
+fn compile_class(chars: &[char], start: usize) -> Option<(Atom, usize)> {
+	let mut i = start + 1;
+	let negated = matches!(chars.get(i), Some('!' | '^'));
+	if negated {
+		i += 1;
+	}
+
+	let mut range: Vec<(char, char)> = Vec::new();
+	let mut first = true;
+	while i < chars.len() {
+		let c = chars[i];
+		if c == ']' && !first {
+			return Some((Atom::Class { negated, range }, i + 1));
+		}
+		first = false;
+
+		if i + 2 < chars.len() && chars[i + 1] == '-' && chars[i + 2] != ']' {
+			range.push((c, chars[i + 2]));
+			i += 3;
+		} else {
+			range.push((c, c));
+			i += 1;
+		}
+	}
+
+	None
+}
+
+/// parse `{n}`, `{n,}` or `{n,m}` at `chars[start]`, return `(min, max, index after '}')`
+fn compile_repeat(chars: &[char], start: usize) -> Option<(usize, Option<usize>, usize)> {
+	if chars.get(start) != Some(&'{') {
+		return None;
+	}
+	let end = start + chars[start..].iter().position(|c| *c == '}')?;
+	let inner: String = chars[start + 1..end].iter().collect();
+
+	let (min, max) = match inner.split_once(',') {
+		None => {
+			let n = inner.trim().parse::<usize>().ok()?;
+			(n, Some(n))
+		}
+		Some((min, max)) if max.trim().is_empty() => (min.trim().parse::<usize>().ok()?, None),
+		Some((min, max)) => (
+			min.trim().parse::<usize>().ok()?,
+			Some(max.trim().parse::<usize>().ok()?),
+		),
+	};
+
+	if max.is_some_and(|max| max < min) {
+		return None;
+	}
+
+	Some((min, max, end + 1))
+}
+
+fn match_from(atom: &[Quantified], token: &[char]) -> bool {
+	let Some((q, rest)) = atom.split_first() else {
+		return token.is_empty();
+	};
+
+	let mut n = 0;
+	while n < q.min {
+		if n >= token.len() || !q.atom.matches(token[n]) {
+			return false;
+		}
+		n += 1;
+	}
+
+	loop {
+		if match_from(rest, &token[n..]) {
+			return true;
+		}
+		if q.max.is_some_and(|max| n >= max) || n >= token.len() || !q.atom.matches(token[n]) {
+			return false;
+		}
+		n += 1;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_pattern_literal() {
+		let p = Pattern::new("abc");
+		assert!(p.matches("abc"));
+		assert!(!p.matches("ab"));
+		assert!(!p.matches("abcd"));
+		assert!(!p.matches(""));
+	}
+
+	#[test]
+	fn test_pattern_glob() {
+		let p = Pattern::new("a?c*");
+		assert!(p.matches("abc"));
+		assert!(p.matches("axcdef"));
+		assert!(!p.matches("ac"));
+
+		let p = Pattern::new("*");
+		assert!(p.matches(""));
+		assert!(p.matches("anything"));
+	}
+
+	#[test]
+	fn test_pattern_class() {
+		let p = Pattern::new("[a-c]");
+		assert!(p.matches("a"));
+		assert!(p.matches("c"));
+		assert!(!p.matches("d"));
+
+		let p = Pattern::new("[!0-9]");
+		assert!(p.matches("a"));
+		assert!(!p.matches("4"));
+
+		let p = Pattern::new("[]a]");
+		assert!(p.matches("]"));
+		assert!(p.matches("a"));
+
+		let p = Pattern::new("[a-]");
+		assert!(p.matches("-"));
+		assert!(p.matches("a"));
+	}
+
+	#[test]
+	fn test_pattern_repeat() {
+		let p = Pattern::new("[0-9]{2}");
+		assert!(p.matches("42"));
+		assert!(!p.matches("4"));
+		assert!(!p.matches("421"));
+
+		let p = Pattern::new("x{1,3}");
+		assert!(!p.matches(""));
+		assert!(p.matches("xxx"));
+		assert!(!p.matches("xxxx"));
+
+		let p = Pattern::new("[0-9]{1,}.[0-9]{1,}");
+		assert!(p.matches("3.14"));
+		assert!(!p.matches(".14"));
+	}
+
+	#[test]
+	fn test_pattern_malformed() {
+		let p = Pattern::new("[abc");
+		assert!(p.matches("[abc"));
+
+		let p = Pattern::new("a{2");
+		assert!(p.matches("a{2"));
+
+		let p = Pattern::new("a{3,1}");
+		assert!(p.matches("a{3,1}"));
+
+		let p = Pattern::new("\\*");
+		assert!(p.matches("*"));
+		assert!(!p.matches("a"));
+	}
+
+	#[test]
+	fn test_pattern_unicode() {
+		let p = Pattern::new("?é[à-ù]");
+		assert!(p.matches("aéè"));
+		assert!(!p.matches("aée"));
+	}
+}
@@ -0,0 +1,176 @@
+use crate::{FileDataKey, SpofedFile};
+
+use hmerr::{Result, ioe};
+
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// reload a `SpofedFile` every time it is modified on disk
+///
+/// the file is polled (modification time and size), no external service is required
+///
+/// # Example
+///
+/// ```no_run
+/// use spof::{Watcher, rule};
+///
+/// use std::ops::ControlFlow;
+/// use std::time::Duration;
+///
+/// rule!(
+///     enum ConfRule {
+///         Port => "port", "PORT", Fixed, Once, "the port to listen on",
+///     }
+/// );
+///
+/// let mut w = Watcher::<ConfRule>::new("service.conf", Some("#")).interval(Duration::from_secs(2));
+///
+/// std::thread::spawn(move || {
+///     // only called with a valid file, errors are printed on stderr
+///     w.watch(|conf| {
+///         println!("new port: {}", conf[ConfRule::Port].data.get_first_token());
+///         ControlFlow::Continue(())
+///     });
+/// });
+/// ```
+pub struct Watcher<K: FileDataKey> {
+	pub path: PathBuf,
+	comment: Option<String>,
+	interval: Duration,
+	last: Option<(SystemTime, u64)>,
+	_key: PhantomData<K>,
+}
+
+impl<K: FileDataKey> Watcher<K> {
+	pub fn new(path: impl Into<PathBuf>, comment: Option<&str>) -> Self {
+		Self {
+			path: path.into(),
+			comment: comment.map(String::from),
+			interval: Duration::from_secs(1),
+			last: None,
+			_key: PhantomData,
+		}
+	}
+
+	/// time to wait between two polls in `watch` (default: 1 second)
+	pub fn interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+
+	/// check the file once
+	///
+	/// * `Ok(Some(file))` - the file changed since the last poll and is valid
+	/// * `Ok(None)` - the file did not change since the last poll
+	/// * `Err(e)` - the file could not be read or is invalid
+	///
+	/// an invalid version is only reported once, until the file changes again
+	pub fn poll(&mut self) -> Result<Option<SpofedFile<K>>> {
+		let name = self.path.to_string_lossy().to_string();
+		let meta = std::fs::metadata(&self.path).map_err(|e| ioe!(name.as_str(), e))?;
+		let modified = meta.modified().map_err(|e| ioe!(name.as_str(), e))?;
+
+		let current = Some((modified, meta.len()));
+		if self.last == current {
+			return Ok(None);
+		}
+		self.last = current;
+
+		let file = SpofedFile::new(&self.path, self.comment.as_deref(), K::build())?;
+		file.check_occurrence()?;
+
+		Ok(Some(file))
+	}
+
+	/// poll the file every `interval` and call `on_change` with every new valid version,
+	/// until `on_change` returns `ControlFlow::Break`
+	///
+	/// errors are printed on stderr, the same error is not printed twice in a row
+	pub fn watch<F>(&mut self, mut on_change: F)
+	where
+		F: FnMut(SpofedFile<K>) -> ControlFlow<()>,
+	{
+		let mut last_error: Option<String> = None;
+
+		loop {
+			match self.poll() {
+				Ok(Some(file)) => {
+					last_error = None;
+					if on_change(file).is_break() {
+						return;
+					}
+				}
+				Ok(None) => {}
+				Err(e) => {
+					let e = format!("{e}");
+					if last_error.as_ref() != Some(&e) {
+						eprintln!("{e}");
+						last_error = Some(e);
+					}
+				}
+			}
+
+			std::thread::sleep(self.interval);
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::rule;
+
+	rule!(
+		enum RuleTest {
+			Port => "port", "PORT", Fixed, Once, "the port",
+		}
+	);
+
+	#[test]
+	fn test_poll() {
+		let path = std::env::temp_dir().join("spof_watch_poll.conf");
+		std::fs::write(&path, "port 80").unwrap();
+
+		let mut w = Watcher::<RuleTest>::new(&path, Some("#"));
+
+		let f = w.poll().unwrap().expect("first poll should load the file");
+		assert_eq!(f[RuleTest::Port].data.get_first_token(), "80");
+		assert!(w.poll().unwrap().is_none());
+
+		std::fs::write(&path, "port 8080").unwrap();
+		let f = w.poll().unwrap().expect("file changed");
+		assert_eq!(f[RuleTest::Port].data.get_first_token(), "8080");
+
+		// missing `port`
+		std::fs::write(&path, "# port 443").unwrap();
+		assert!(w.poll().is_err());
+		assert!(w.poll().unwrap().is_none());
+
+		std::fs::remove_file(&path).unwrap();
+		assert!(w.poll().is_err());
+	}
+
+	#[test]
+	fn test_watch() {
+		let path = std::env::temp_dir().join("spof_watch_watch.conf");
+		std::fs::write(&path, "port 80").unwrap();
+
+		let mut port = Vec::new();
+		Watcher::<RuleTest>::new(&path, Some("#"))
+			.interval(Duration::from_millis(1))
+			.watch(|f| {
+				port.push(f[RuleTest::Port].data.get_first_token().clone());
+				if port.len() == 1 {
+					std::fs::write(&path, "port 4242").unwrap();
+					ControlFlow::Continue(())
+				} else {
+					ControlFlow::Break(())
+				}
+			});
+		std::fs::remove_file(&path).unwrap();
+
+		assert_eq!(port, ["80", "4242"]);
+	}
+}
@@ -0,0 +1,234 @@
+use hmerr::ParseFileError;
+use proptest::prelude::*;
+use spof::{
+	Constraint, ExpectedLine, ExpectedSize, FileData, FileDataKey, Format, FoundLine, KeyData,
+	Keyword, Occurrence, Order, SpofedFile,
+};
+
+use std::cell::RefCell;
+use std::str::FromStr;
+
+/// a rule generated at runtime
+#[derive(Debug, Clone)]
+struct RuleSpec {
+	keyword: String,
+	format: Vec<String>,
+	size: ExpectedSize,
+	occurrence: Occurrence,
+	order: Option<Order>,
+}
+
+thread_local! {
+	static RULE: RefCell<Vec<RuleSpec>> = const { RefCell::new(Vec::new()) };
+}
+
+/// `FileDataKey` backed by the rules in `RULE`
+#[derive(Debug, Clone, Copy)]
+struct Key(usize);
+
+impl From<Key> for usize {
+	fn from(k: Key) -> usize {
+		k.0
+	}
+}
+
+impl FromStr for Key {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		RULE.with(|r| {
+			r.borrow()
+				.iter()
+				.position(|spec| spec.keyword == s)
+				.map(Key)
+				.ok_or(())
+		})
+	}
+}
+
+impl FileDataKey for Key {
+	fn build() -> FileData<Self> {
+		RULE.with(|r| {
+			FileData::new(
+				r.borrow()
+					.iter()
+					.map(|spec| {
+						let el = ExpectedLine::new(
+							Keyword::new(spec.keyword.as_str(), "generated"),
+							Format::new(spec.format.join(" "), spec.size),
+							spec.occurrence,
+						);
+						let el = match &spec.order {
+							Some(order) => el.with_order(order.clone()),
+							None => el,
+						};
+						KeyData::new(FoundLine::new(), el)
+					})
+					.collect(),
+			)
+		})
+	}
+}
+
+fn keyword() -> impl Strategy<Value = String> {
+	"[a-z]{1,6}"
+}
+
+fn format_token() -> impl Strategy<Value = String> {
+	prop_oneof![
+		4 => "[A-Z]{1,3}",
+		1 => prop::collection::vec("[a-z0-9]{1,3}", 1..4).prop_map(|v| v.join("|")),
+		1 => "[A-Z]{1,3}:/[a-z0-9?*]{1,4}/",
+		1 => "[A-Z]{1,3}:[A-Z]{1,3}",
+		1 => "\\[[A-Z]{1,3}\\]",
+	]
+}
+
+fn size() -> impl Strategy<Value = ExpectedSize> {
+	prop_oneof![
+		Just(ExpectedSize::Fixed),
+		Just(ExpectedSize::Undefined),
+		(0..4usize, 0..4usize).prop_map(|(a, b)| ExpectedSize::Range(a.min(b), a.max(b))),
+	]
+}
+
+fn occurrence() -> impl Strategy<Value = Occurrence> {
+	prop_oneof![
+		Just(Occurrence::Once),
+		Just(Occurrence::Optional),
+		Just(Occurrence::ZeroOrMore),
+		Just(Occurrence::OneOrMore),
+		(0..4usize).prop_map(Occurrence::Exactly),
+		(0..4usize, 0..4usize).prop_map(|(a, b)| Occurrence::Range(a.min(b), a.max(b))),
+	]
+}
+
+fn order() -> impl Strategy<Value = Option<Order>> {
+	prop_oneof![
+		4 => Just(None),
+		1 => Just(Some(Order::First)),
+		1 => Just(Some(Order::Last)),
+		1 => keyword().prop_map(|k| Some(Order::Before(k))),
+		1 => keyword().prop_map(|k| Some(Order::After(k))),
+		1 => (0..20usize).prop_map(|n| Some(Order::BeforeLine(n))),
+	]
+}
+
+fn rule() -> impl Strategy<Value = Vec<RuleSpec>> {
+	prop::collection::vec(
+		(
+			keyword(),
+			prop::collection::vec(format_token(), 0..4),
+			size(),
+			occurrence(),
+			order(),
+		)
+			.prop_map(|(keyword, format, size, occurrence, order)| RuleSpec {
+				keyword,
+				format,
+				size,
+				occurrence,
+				order,
+			}),
+		1..6,
+	)
+	.prop_map(|mut rule| {
+		rule.sort_by(|a, b| a.keyword.cmp(&b.keyword));
+		rule.dedup_by(|a, b| a.keyword == b.keyword);
+		rule
+	})
+}
+
+/// lines made of known keywords, random words, comments and odd whitespace
+fn content(rule: &[RuleSpec]) -> impl Strategy<Value = String> + use<> {
+	let known: Vec<String> = rule.iter().map(|r| r.keyword.clone()).collect();
+	let word = prop_oneof![
+		4 => prop::sample::select(known),
+		2 => "[a-z0-9|?*#:\\[\\]-]{1,4}",
+		1 => "\\PC{1,3}",
+	];
+	let line = (
+		"[ \t]{0,2}",
+		prop::collection::vec(word, 0..6),
+		prop_oneof![Just(String::new()), "#\\PC{0,8}"],
+	)
+		.prop_map(|(indent, word, comment)| format!("{indent}{}{comment}", word.join(" ")));
+
+	prop::collection::vec(line, 0..12).prop_map(|l| l.join("\n"))
+}
+
+fn rule_and_content() -> impl Strategy<Value = (Vec<RuleSpec>, String)> {
+	rule().prop_flat_map(|rule| {
+		let content = content(&rule);
+		(Just(rule), content)
+	})
+}
+
+fn parse(rule: &[RuleSpec], content: &str) -> hmerr::Result<SpofedFile<Key>> {
+	RULE.with(|r| *r.borrow_mut() = rule.to_vec());
+	SpofedFile::from_reader("generated", content.as_bytes(), Some("#"), Key::build())
+}
+
+fn write(f: &SpofedFile<Key>) -> String {
+	let mut out = Vec::new();
+	f.write(&mut out).unwrap();
+	String::from_utf8(out).unwrap()
+}
+
+fn tokens(f: &SpofedFile<Key>) -> Vec<Vec<Vec<String>>> {
+	f.data()
+		.iter()
+		.map(|kd| kd.data.0.iter().map(|pl| pl.0.clone()).collect())
+		.collect()
+}
+
+proptest! {
+	#[test]
+	fn parse_never_panics((rule, content) in rule_and_content()) {
+		if let Ok(f) = parse(&rule, &content) {
+			let _ = f.check_occurrence();
+		}
+	}
+
+	#[test]
+	fn error_line_in_range((rule, content) in rule_and_content()) {
+		let line_count = content.lines().count();
+
+		let e = match parse(&rule, &content) {
+			Ok(f) => match f.check_occurrence() {
+				Ok(_) => return Ok(()),
+				Err(e) => e,
+			},
+			Err(e) => e,
+		};
+
+		let mut e: Option<&(dyn std::error::Error + 'static)> = Some(e.as_ref());
+		while let Some(err) = e {
+			if let Some(pfe) = err.downcast_ref::<ParseFileError>()
+				&& let Some(index) = pfe.line.as_ref().and_then(|l| l.index)
+			{
+				prop_assert!(index < line_count, "line {} out of {} lines", index, line_count);
+			}
+			e = err.source();
+		}
+	}
+
+	#[test]
+	fn write_round_trip((rule, content) in rule_and_content()) {
+		let Ok(f) = parse(&rule, &content) else {
+			return Ok(());
+		};
+
+		let written = write(&f);
+		let again = parse(&rule, &written).expect("written file should parse");
+
+		prop_assert_eq!(tokens(&f), tokens(&again));
+		prop_assert_eq!(written, write(&again));
+	}
+
+	#[test]
+	fn constraint_never_panics(pattern in "\\PC{0,12}", token in "\\PC{0,12}") {
+		let _ = Constraint::pattern(pattern.as_str()).matches(&token);
+		let _ = Constraint::parse(&pattern);
+	}
+}